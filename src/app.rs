@@ -1,13 +1,26 @@
-use crate::message::{Message, ColorOption, FullscreenKey};
+use crate::message::{Message, ColorOption, FullscreenKey, FilterEdit, ExportFormat, TrainingEdit, WarmupEdit};
 use crate::infrastructure::{Config, ColorTheme, get_config_path, get_config_file, 
-save_config_file, open_file_dialog, open_file_dialog_font, get_font, file_processor};
+save_config_file, open_file_dialog, open_file_dialog_font, get_font, file_processor,
+archive_entries, is_archive, is_readable, save_text, unused_text_name, save_text_meta, load_font_file, FONT_EXTENSIONS,
+open_folder_dialog, library_texts, scan_inbox, import_file, FileStamp, InboxSeen, load_inbox_seen, save_inbox_seen, FilterRule, filter_presets, compile_filters, filter_chapters, new_filter_id,
+save_file_dialog, highlights_to_markdown, highlights_to_json, search_words, SearchDocument,
+LibraryIndex, load_library_index, save_library_index, search_library, detect_headings, load_sessions, save_session, finish_session, today_total, reading_streaks, ReadingGoal,
+QuizSettings, make_quiz, TrainingSettings, training_wpm, next_baseline, MIN_WPM, MAX_WPM, WarmupSettings,
+PauseMode, BoundaryPause, AutoPauseSettings, ScrollMode, text_boundaries,
+Action, KeyBinding, default_key_bindings, key_spec, key_conflict,
+NamedTheme, parse_theme_file, theme_file, open_theme_dialog, AutoTheme, ThemeMode};
 use iced::{
-    keyboard::{self, Key, key::Named, Event,},
+    keyboard::{self, Event,},
+    mouse::ScrollDelta,
+    system, theme,
     animation::{Animation},
     window::{self, Mode::{Fullscreen, Windowed}},
-    time, Task, Subscription,
+    time, event, Task, Subscription,
 };
-use crate::style::rgb_to_hex;
+use crate::style::{rgb_to_hex, hex_to_rgb, theme_presets, contrast_ratio, nearest_contrast, AA_CONTRAST, AAA_CONTRAST};
+use iced::Color;
+use chrono::Timelike;
+use crate::model::{TextMeta, Cue, ArchiveEntry, LoadedText, LibraryEntry, Bookmark, Highlight, SearchResult, TocEntry, LibrarySearchResult, ReadingSession, SessionSummary, Quiz, Boundary, Sidebar};
 
 pub struct DisplayColor {
     pub background: bool,
@@ -18,6 +31,53 @@ pub struct DisplayColor {
     pub danger: bool
 }
 
+// A color below the WCAG contrast level against the background,
+// with the closest color that reaches it, or the best available one
+pub struct ContrastWarning {
+    pub option: ColorOption,
+    pub ratio: f32,
+    pub level: &'static str,
+    pub target: f32,
+    pub suggestion: Color,
+    pub reached: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Screen {
+    Reader,
+    Library,
+    Stripped,
+    Filters,
+    Highlights,
+    Statistics,
+    Summary,
+    Quiz,
+    Keys,
+    Settings,
+}
+
+pub struct SearchState {
+    pub query: String,
+    pub regex: bool,
+    pub case_insensitive: bool,
+    pub results: Vec<SearchResult>,
+    pub selected: Option<usize>,
+    pub error: Option<String>,
+    // The loaded text prepared for searching
+    document: SearchDocument,
+}
+
+pub const SEARCH_INPUT: &str = "search";
+// Resuming within this many seconds continues the same reading session
+const SESSION_GAP: i64 = 300;
+// Words read between two saves of the running session
+const SESSION_SAVE_WORDS: usize = 100;
+// Pixels of trackpad scrolling per word step and per sentence swipe
+const SCROLL_STEP: f32 = 40.0;
+const SWIPE_STEP: f32 = 150.0;
+// Fullscreen controls stay visible this long after the mouse stops
+const CONTROLS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
 // Custom state
 pub struct State {
     config: Config,
@@ -29,12 +89,94 @@ pub struct State {
     pub text_instant: time::Instant,
     pub fullscreen: bool,
     pub current_font: iced::Font,
-    pub text_title: String,
+    pub text_name: String,
+    pub text_meta: TextMeta,
     pub text_loader: bool,
+    cue_delays: Option<Vec<u64>>,
+    pub archive: Option<Vec<ArchiveEntry>>,
+    text_content: String,
+    // Clipboard and stdin texts stay out of the library until saved
+    pub text_unsaved: bool,
+    pub file_hover: bool,
+    pub screen: Screen,
+    pub library: Vec<LibraryEntry>,
+    // Inbox files as they were when imported, as seen on the last scan, and being imported
+    inbox_seen: InboxSeen,
+    inbox_pending: std::collections::HashMap<std::path::PathBuf, FileStamp>,
+    inbox_importing: std::collections::HashSet<std::path::PathBuf>,
+    pub sidebar: Option<Sidebar>,
+    pub bookmark_label: String,
+    pub search: SearchState,
+    // Loaded in the background at startup, texts added before that wait in the queue
+    library_index: Option<std::sync::Arc<LibraryIndex>>,
+    index_queue: Vec<String>,
+    // index.json is written in the background, once more when the index changed meanwhile
+    index_saving: bool,
+    index_dirty: bool,
+    pub library_query: String,
+    pub library_results: Vec<LibrarySearchResult>,
+    // Word and phrase to jump to once the opened text is loaded
+    pending_jump: Option<(usize, String)>,
+    // Word where each table of contents entry starts
+    toc_starts: Vec<usize>,
+    session: Option<ReadingSession>,
+    // When reading last resumed, None while paused
+    reading_since: Option<time::Instant>,
+    pub sessions: Vec<ReadingSession>,
+    // Words and reading time of today's sessions before the current one
+    today_base: (usize, u64),
+    pub summary: Option<SessionSummary>,
+    pub quiz: Option<Quiz>,
+    pub quiz_choice: Option<usize>,
+    // Where the words of the next question start, and how many were read since the last one
+    quiz_start: usize,
+    quiz_words: usize,
+    // Word where the last warm-up started
+    warmup_from: Option<usize>,
+    // Words followed by a paragraph, section or chapter break
+    boundaries: std::collections::HashMap<usize, Boundary>,
+    // Boundary word where reading last stopped to wait for a key
+    boundary_wait: Option<usize>,
+    // Binding waiting for its new key, and why the last key was refused
+    pub rebinding: Option<usize>,
+    pub key_notice: Option<String>,
+    // Scrolling not yet turned into whole steps, and the last mouse move in fullscreen
+    scroll_offset: (f32, f32),
+    controls_moved: Option<time::Instant>,
+    controls_hovered: bool,
+    // Name typed for saving the palette, and the outcome of the last theme action
+    pub theme_name: String,
+    pub theme_notice: Option<String>,
+    // Problem shown above every screen until dismissed, and a font waiting to be loaded
+    pub notice: Option<String>,
+    pending_font: Option<String>,
+    // Desktop color scheme once reported, and whether the auto mode shows its dark theme
+    system_mode: Option<theme::Mode>,
+    auto_dark: Option<bool>,
+    // Colors on screen, resolved again only when the theme, the mode, the time or the desktop scheme changes
+    active_theme: ColorTheme,
+    pub contrast_warnings: Vec<ContrastWarning>,
 }
 
 impl State {
     pub fn get_state_theme(&self) -> &ColorTheme {
+        &self.active_theme
+    }
+    // The light or dark theme of the auto mode, or the manual palette
+    fn resolve_theme(&mut self) {
+        let auto = &self.config.auto_theme;
+        let name = self.auto_dark.map(|dark| if dark { &auto.dark } else { &auto.light });
+        self.active_theme = name
+        .and_then(|name| {
+            self.config.themes.iter()
+            .find(|theme| &theme.name == name)
+            .map(|theme| theme.colors.clone())
+            .or_else(|| theme_presets().into_iter().find(|theme| &theme.name == name).map(|theme| theme.colors))
+        })
+        .unwrap_or_else(|| self.config.theme.clone());
+    }
+    // Colors edited in settings, kept apart from the auto mode
+    pub fn get_manual_theme(&self) -> &ColorTheme {
         &self.config.theme
     }
     pub fn get_text_size(&self) -> f32 {
@@ -49,6 +191,332 @@ impl State {
     pub fn set_velocity(&mut self, v: u32) {
         self.config.velocity = v;
     }
+    pub fn get_subtitle_timing(&self) -> bool {
+        self.config.subtitle_timing
+    }
+    pub fn is_paused(&self) -> bool {
+        self.pause
+    }
+    // Milliseconds the current word stays on screen
+    pub fn get_word_delay(&self) -> u64 {
+        if self.config.subtitle_timing
+            && let (Some(delays), Some(index)) = (&self.cue_delays, self.index)
+            && let Some(delay) = delays.get(index) {
+                return *delay;
+        }
+        let delay = match self.get_training_speed() {
+            Some((wpm, _)) => 60_000 / wpm as u64,
+            None => self.get_velocity().into(),
+        };
+        // Slower at first after a resume, a seek or a chapter start
+        let warmup = self.config.warmup;
+        let delay = match (self.warmup_from, self.index) {
+            (Some(from), Some(index)) if index >= from && index < from + warmup.words as usize => {
+                let progress = (index - from) as f32 / warmup.words as f32;
+                let eased = 1.0 - (1.0 - progress).powi(2);
+                let start = warmup.start_speed.clamp(10, 100) as f32 / 100.0;
+                (delay as f32 / (start + (1.0 - start) * eased)) as u64
+            },
+            _ => delay,
+        };
+        // A timed pause at a boundary comes on top, warm-up or not
+        match self.get_boundary_pause() {
+            Some(pause) if pause.mode == PauseMode::Timed => delay + pause.duration as u64,
+            _ => delay,
+        }
+    }
+    // Moves to a word, reading picks up from there with a warm-up
+    fn seek(&mut self, index: usize) {
+        self.index = Some(index);
+        self.warmup_from = Some(index);
+    }
+    pub fn get_scroll_mode(&self) -> ScrollMode {
+        self.config.scroll
+    }
+    pub fn controls_visible(&self) -> bool {
+        self.controls_hovered || self.controls_moved.is_some_and(|moved| self.text_instant.duration_since(moved) < CONTROLS_TIMEOUT)
+    }
+    pub fn get_key_bindings(&self) -> &[KeyBinding] {
+        &self.config.keys
+    }
+    pub fn get_auto_pause(&self) -> AutoPauseSettings {
+        self.config.auto_pause
+    }
+    pub fn get_warmup(&self) -> WarmupSettings {
+        self.config.warmup
+    }
+    pub fn get_training(&self) -> TrainingSettings {
+        self.config.training
+    }
+    // Words per minute of the training session and whether it is sprinting
+    pub fn get_training_speed(&self) -> Option<(u32, bool)> {
+        if !self.config.training.enabled {
+            return None;
+        }
+        let reading_ms = self.session.as_ref().map(|s| s.reading_ms).unwrap_or(0)
+        + self.reading_since.map(|since| since.elapsed().as_millis() as u64).unwrap_or(0);
+        Some(training_wpm(&self.config.training, reading_ms))
+    }
+    pub fn get_strip_boilerplate(&self) -> bool {
+        self.config.strip_boilerplate
+    }
+    pub fn get_strip_back_matter(&self) -> bool {
+        self.config.strip_back_matter
+    }
+    // Presets first, then the saved themes
+    fn get_themes(&self) -> Vec<NamedTheme> {
+        theme_presets().into_iter()
+        .chain(self.config.themes.iter().cloned())
+        .collect()
+    }
+    pub fn get_theme_names(&self) -> Vec<String> {
+        self.get_themes().into_iter().map(|theme| theme.name).collect()
+    }
+    // Name of the theme matching the current colors, None once they are edited
+    pub fn get_current_theme_name(&self) -> Option<String> {
+        self.get_themes().into_iter()
+        .find(|theme| theme.colors == self.config.theme)
+        .map(|theme| theme.name)
+    }
+    pub fn get_auto_theme(&self) -> &AutoTheme {
+        &self.config.auto_theme
+    }
+    // Picks the light or dark theme of the auto mode, waiting for the desktop color scheme in system mode
+    fn apply_auto_theme(&mut self) {
+        let auto = &self.config.auto_theme;
+        self.auto_dark = match auto.mode {
+            ThemeMode::Manual => None,
+            ThemeMode::System => match self.system_mode {
+                Some(mode) => Some(mode == theme::Mode::Dark),
+                None => return,
+            },
+            ThemeMode::Schedule => {
+                let now = chrono::Local::now();
+                Some(auto.scheduled_dark(now.hour() * 60 + now.minute()))
+            },
+        };
+        self.resolve_theme();
+    }
+    pub fn is_saved_theme(&self, name: &str) -> bool {
+        self.config.themes.iter().any(|theme| theme.name == name)
+    }
+    pub fn get_font_name(&self) -> &str {
+        &self.config.font
+    }
+    pub fn get_history_len(&self) -> usize {
+        self.config.text_history.len()
+    }
+    pub fn get_inbox(&self) -> Option<&str> {
+        self.config.inbox.as_deref()
+    }
+    pub fn get_filters(&self) -> &[FilterRule] {
+        &self.config.filters
+    }
+    pub fn get_text_len(&self) -> usize {
+        self.text.as_ref().map(|text| text.len()).unwrap_or(0)
+    }
+    fn save_index(&mut self) -> Task<Message> {
+        let Some(index) = &self.library_index else { return Task::none() };
+        if self.index_saving {
+            self.index_dirty = true;
+            return Task::none();
+        }
+        self.index_saving = true;
+        self.index_dirty = false;
+        Task::perform(save_library_index(get_config_path(), index.clone()), |_| Message::IndexSaved)
+    }
+    fn save_meta(&self) {
+        if !self.text_unsaved && !self.text_name.is_empty() {
+            let config_path = get_config_path();
+            save_text_meta(&config_path, &self.text_name, &self.text_meta);
+        }
+    }
+    fn run_search(&mut self) {
+        let text = self.text.as_deref().unwrap_or_default();
+        match search_words(text, &self.search.document, &self.search.query, self.search.regex, self.search.case_insensitive) {
+            Ok(results) => {
+                self.search.results = results;
+                self.search.error = None;
+            },
+            Err(e) => {
+                self.search.results.clear();
+                self.search.error = Some(e);
+            }
+        }
+        self.search.selected = None;
+    }
+    fn jump_to_result(&mut self, i: usize) {
+        if let Some(result) = self.search.results.get(i) {
+            self.seek(result.index);
+            self.search.selected = Some(i);
+        }
+    }
+    pub fn has_text(&self) -> bool {
+        self.text.is_some()
+    }
+    // Applies the filter rules to the loaded text and splits it into words
+    fn split_text(&mut self) {
+        self.toc_starts.clear();
+        // Subtitles are left unfiltered so cues keep matching their words
+        let content = if self.cue_delays.is_some() {
+            self.text_content.clone()
+        } else {
+            // Chapters start on their first line, tracked through the filters as words
+            let mut line_starts: Vec<usize> = vec![0];
+            line_starts.extend(self.text_content.match_indices('\n').map(|(i, _)| i + 1));
+            let starts: Vec<usize> = self.text_meta.toc.iter()
+            .map(|entry| line_starts.get(entry.line).copied().unwrap_or(self.text_content.len()))
+            .collect();
+            let filters = compile_filters(&self.config.filters, &self.text_meta.disabled_filters);
+            let (content, toc_starts) = filter_chapters(&self.text_content, &starts, &filters);
+            self.toc_starts = toc_starts;
+            content
+        };
+        // 1. Split text string without counting white spaces
+        // 2. Convert each &str slice to String, then to Box<str>
+        // 3. Collects into a Vec
+        let text: Vec<Box<str>> = content
+            .split_whitespace()
+            .map(|s: &str| s.to_string().into_boxed_str())
+            .collect();
+        self.index = self.index.map(|index| index.min(text.len()));
+        self.search.document = SearchDocument::new(&text);
+        self.text = Some(text);
+
+        // Chapters are the outermost table of contents entries, the others are sections
+        self.boundaries = text_boundaries(&content).into_iter().collect();
+        let top = self.text_meta.toc.iter().map(|entry| entry.level).min().unwrap_or(1);
+        for (entry, start) in self.text_meta.toc.iter().zip(&self.toc_starts) {
+            if *start > 0 {
+                let boundary = if entry.level == top { Boundary::Chapter } else { Boundary::Section };
+                let current = self.boundaries.entry(start - 1).or_insert(boundary);
+                *current = (*current).max(boundary);
+            }
+        }
+    }
+    // Pause settings of the boundary after the current word
+    fn get_boundary_pause(&self) -> Option<BoundaryPause> {
+        let boundary = self.index.and_then(|index| self.boundaries.get(&index))?;
+        Some(self.config.auto_pause.get(*boundary))
+    }
+    // Pausing and resuming go through here to keep the reading session up to date
+    fn set_pause(&mut self, pause: bool) {
+        self.pause = pause;
+        if pause {
+            self.end_reading();
+        } else if self.has_text() {
+            self.begin_reading();
+        }
+    }
+    fn begin_reading(&mut self) {
+        if self.reading_since.is_some() {
+            return;
+        }
+        self.warmup_from = self.index;
+        let now = chrono::Local::now().timestamp();
+        match &mut self.session {
+            Some(session) if session.text == self.text_name && now - session.end < SESSION_GAP => session.pauses += 1,
+            _ => {
+                let today = today_total(&load_sessions(&get_config_path()));
+                self.today_base = (today.words, today.reading_ms);
+                self.session = Some(ReadingSession {
+                    text: self.text_name.clone(),
+                    title: self.get_title().to_string(),
+                    start: now,
+                    end: now,
+                    reading_ms: 0,
+                    words: 0,
+                    pauses: 0,
+                    rewinds: 0,
+                    questions: 0,
+                    correct: 0,
+                });
+            }
+        }
+        self.reading_since = Some(time::Instant::now());
+    }
+    fn end_reading(&mut self) {
+        let Some(since) = self.reading_since.take() else { return };
+        if let Some(session) = &mut self.session {
+            session.reading_ms += since.elapsed().as_millis() as u64;
+            session.end = chrono::Local::now().timestamp();
+        }
+        self.save_session();
+    }
+    pub fn get_quiz_settings(&self) -> QuizSettings {
+        self.config.quiz
+    }
+    // A question is asked at the end of each chapter and every interval words
+    fn quiz_due(&self, index: usize) -> bool {
+        let settings = self.config.quiz;
+        let chapter_end = index > 0 && !self.toc_starts.is_empty()
+        && (self.toc_starts.contains(&index) || index == self.get_text_len());
+        settings.enabled && (chapter_end || settings.interval > 0 && self.quiz_words >= settings.interval as usize)
+    }
+    fn start_quiz(&mut self, index: usize) {
+        let Some(text) = &self.text else { return };
+        // After a jump back the words since the last question are unknown
+        let start = if self.quiz_start < index { self.quiz_start } else { index.saturating_sub(self.config.quiz.interval.max(100) as usize) };
+        self.quiz = make_quiz(text, start, index);
+        self.quiz_choice = None;
+        self.quiz_start = index;
+        self.quiz_words = 0;
+        if self.quiz.is_some() {
+            self.set_pause(true);
+            self.screen = Screen::Quiz;
+        }
+    }
+    pub fn has_session(&self) -> bool {
+        self.session.is_some()
+    }
+    pub fn get_goal(&self) -> ReadingGoal {
+        self.config.goal
+    }
+    // Words and reading time of today, the running session included
+    pub fn get_today_reading(&self) -> (usize, u64) {
+        let (mut words, mut reading_ms) = self.today_base;
+        if let Some(session) = &self.session {
+            words += session.words;
+            reading_ms += session.reading_ms + self.reading_since.map(|since| since.elapsed().as_millis() as u64).unwrap_or(0);
+        }
+        (words, reading_ms)
+    }
+    // Saves the session as it is now, the running part included
+    fn save_session(&self) {
+        let Some(session) = &self.session else { return };
+        if session.words == 0 {
+            return;
+        }
+        let mut session = session.clone();
+        if let Some(since) = self.reading_since {
+            session.reading_ms += since.elapsed().as_millis() as u64;
+            session.end = chrono::Local::now().timestamp();
+        }
+        save_session(&get_config_path(), &session);
+    }
+    // Entries with how much of them was read, from 0 to 1
+    pub fn get_contents(&self) -> Vec<(&TocEntry, f32)> {
+        let index = self.index.unwrap_or(0);
+        let len = self.get_text_len();
+        self.text_meta.toc.iter().zip(&self.toc_starts).enumerate()
+        .map(|(i, (entry, start))| {
+            let end = self.toc_starts.get(i + 1).copied().unwrap_or(len).max(*start);
+            let progress = if index >= end {
+                1.0
+            } else if index <= *start {
+                0.0
+            } else {
+                (index - start) as f32 / (end - start) as f32
+            };
+            (entry, progress)
+        })
+        .collect()
+    }
+    // Last entry started before the current word
+    pub fn get_current_chapter(&self) -> Option<usize> {
+        let index = self.index.unwrap_or(0);
+        self.toc_starts.iter().rposition(|start| *start <= index)
+    }
     pub fn get_word(&self) -> [&str; 3] {
         if let (Some(text), Some(index)) = (&self.text, &self.index) 
             && *index < text.len() {
@@ -61,13 +529,34 @@ impl State {
         }
     }
     pub fn get_title(&self) -> &str {
-        &self.text_title
+        if self.text_meta.title.is_empty() {
+            &self.text_name
+        } else {
+            &self.text_meta.title
+        }
+    }
+}
+
+// Text that didn't come from a file, it goes through the same path as a loaded one
+fn adhoc_text(content: String, prefix: &str, title: &str) -> LoadedText {
+    let seconds = std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0);
+    let toc = detect_headings(&content);
+    LoadedText {
+        content,
+        name: format!("{}-{}", prefix, seconds),
+        meta: TextMeta { title: title.to_string(), toc, ..Default::default() },
+        index: 0,
+        adhoc: true,
     }
 }
 
-pub fn new() -> (State, Task<Message>) {
+pub fn new(stdin_text: Option<String>) -> (State, Task<Message>) {
     let config_path = get_config_path();
     let config = get_config_file(config_path);
+    let active_theme = config.theme.clone();
     let state = State {
         current_font: iced::Font::with_name(config.font.clone().leak()),
         config,
@@ -87,14 +576,75 @@ pub fn new() -> (State, Task<Message>) {
         .easing(iced::animation::Easing::EaseInCirc),
         text_instant: time::Instant::now(),
         fullscreen: false,
-        text_title: "".to_string(),
+        text_name: "".to_string(),
+        text_meta: TextMeta::default(),
         text_loader: false,
+        cue_delays: None,
+        archive: None,
+        text_content: String::new(),
+        text_unsaved: false,
+        file_hover: false,
+        screen: Screen::Reader,
+        library: Vec::new(),
+        inbox_seen: load_inbox_seen(&get_config_path()),
+        inbox_pending: std::collections::HashMap::new(),
+        inbox_importing: std::collections::HashSet::new(),
+        sidebar: None,
+        bookmark_label: String::new(),
+        search: SearchState {
+            query: String::new(),
+            regex: false,
+            case_insensitive: true,
+            results: Vec::new(),
+            selected: None,
+            error: None,
+            document: SearchDocument::default(),
+        },
+        library_index: None,
+        index_queue: Vec::new(),
+        index_saving: false,
+        index_dirty: false,
+        library_query: String::new(),
+        library_results: Vec::new(),
+        pending_jump: None,
+        toc_starts: Vec::new(),
+        session: None,
+        reading_since: None,
+        sessions: Vec::new(),
+        today_base: (0, 0),
+        summary: None,
+        quiz: None,
+        quiz_choice: None,
+        quiz_start: 0,
+        quiz_words: 0,
+        warmup_from: None,
+        boundaries: std::collections::HashMap::new(),
+        boundary_wait: None,
+        rebinding: None,
+        key_notice: None,
+        scroll_offset: (0.0, 0.0),
+        controls_moved: None,
+        controls_hovered: false,
+        theme_name: String::new(),
+        theme_notice: None,
+        notice: None,
+        pending_font: None,
+        system_mode: None,
+        auto_dark: None,
+        active_theme,
+        contrast_warnings: Vec::new(),
     };
+    let stdin_task = match stdin_text {
+        Some(content) => Task::done(Message::FileLoaded(Some(adhoc_text(content, "stdin", "Standard input")))),
+        None => Task::none(),
+    };
+    let index_task = Task::perform(load_library_index(get_config_path()), Message::IndexLoaded);
+    let theme_task = Task::batch([system::theme().map(Message::SystemThemeChanged), Task::done(Message::ThemeTick)]);
     let config_path = get_config_path();
     if let Some((_name, bytes)) = get_font(config_path, &state.config) {
-        (state, iced::font::load(bytes).map(Message::FontChanged))
+        (state, Task::batch([iced::font::load(bytes).map(Message::FontChanged), stdin_task, index_task, theme_task]))
     } else {
-        (state, Task::none())
+        (state, Task::batch([stdin_task, index_task, theme_task]))
     }
 }
 
@@ -113,15 +663,15 @@ pub fn update(current_state: &mut State, message: Message) -> Task<Message> {
                 current_state.display.primary = true;
                 Task::none()
             },
-            ColorOption::_Success => {
+            ColorOption::Success => {
                 current_state.display.success = true;
                 Task::none()
             },
-            ColorOption::_Warning => {
+            ColorOption::Warning => {
                 current_state.display.warning = true;
                 Task::none()
             },
-            ColorOption::_Danger => {
+            ColorOption::Danger => {
                 current_state.display.danger = true;
                 Task::none()
             },
@@ -139,92 +689,954 @@ pub fn update(current_state: &mut State, message: Message) -> Task<Message> {
                 current_state.display.primary = false;
                 Task::none()
             },
-            ColorOption::_Success => {
+            ColorOption::Success => {
                 current_state.display.success = false;
                 Task::none()
             },
-            ColorOption::_Warning => {
+            ColorOption::Warning => {
                 current_state.display.warning = false;
                 Task::none()
             },
-            ColorOption::_Danger => {
+            ColorOption::Danger => {
                 current_state.display.danger = false;
                 Task::none()
             },
         },
-        Message::SubmitColor(color, color_option) => match color_option {
-            ColorOption::Background => {
-                current_state.config.theme.background = rgb_to_hex(color);
-                current_state.display.background = false;
+        Message::SubmitColor(color, color_option) => {
+            match &color_option {
+                ColorOption::Background => {
+                    current_state.config.theme.background = rgb_to_hex(color);
+                    current_state.display.background = false;
+                    let config_path = get_config_path();
+                    save_config_file(config_path, &current_state.config);
+                },
+                ColorOption::Text => {
+                    current_state.config.theme.text = rgb_to_hex(color);
+                    current_state.display.text = false;
+                    let config_path = get_config_path();
+                    save_config_file(config_path, &current_state.config);
+                },
+                ColorOption::Primary => {
+                    current_state.config.theme.primary = rgb_to_hex(color);
+                    current_state.display.primary = false;
+                    let config_path = get_config_path();
+                    save_config_file(config_path, &current_state.config);
+                },
+                ColorOption::Success => {
+                    current_state.config.theme.success = rgb_to_hex(color);
+                    current_state.display.success = false;
+                    let config_path = get_config_path();
+                    save_config_file(config_path, &current_state.config);
+                },
+                ColorOption::Warning => {
+                    current_state.config.theme.warning = rgb_to_hex(color);
+                    current_state.display.warning = false;
+                    let config_path = get_config_path();
+                    save_config_file(config_path, &current_state.config);
+                },
+                ColorOption::Danger => {
+                    current_state.config.theme.danger = rgb_to_hex(color);
+                    current_state.display.danger = false;
+                    let config_path = get_config_path();
+                    save_config_file(config_path, &current_state.config);
+                },
+            }
+            current_state.contrast_warnings = contrast_warnings(&current_state.config.theme);
+            current_state.resolve_theme();
+            Task::none()
+        },
+        Message::FileDialog => {
+            if let Some((data, filename)) = open_file_dialog() {
+            
                 let config_path = get_config_path();
-                save_config_file(config_path, &current_state.config);
+
+                // Running off the main thread
+                current_state.text_loader = true;
+                if is_archive(&filename) {
+                    return Task::perform(archive_entries(data, filename), Message::ArchiveLoaded)
+                }
+                return Task::perform(file_processor(current_state.config.text_history.clone(), config_path, data, filename, current_state.config.preprocess()), Message::FileLoaded)
+            }
+            Task::none()
+        },
+        Message::ArchiveLoaded(entries) => {
+            current_state.text_loader = false;
+            current_state.archive = Some(entries);
+            Task::none()
+        },
+        Message::ArchiveEntrySelected(i) => {
+            if let Some(entry) = current_state.archive.take().and_then(|mut entries| (i < entries.len()).then(|| entries.swap_remove(i))) {
+                let config_path = get_config_path();
+                let filename = entry.name.rsplit('/').next().unwrap_or(&entry.name).to_string();
+                current_state.text_loader = true;
+                return Task::perform(file_processor(current_state.config.text_history.clone(), config_path, entry.data, filename, current_state.config.preprocess()), Message::FileLoaded)
+            }
+            Task::none()
+        },
+        Message::ArchiveClosed => {
+            current_state.archive = None;
+            Task::none()
+        },
+        Message::FileLoaded(text) => {
+            current_state.text_loader = false;
+            if let Some(loaded) = text {
+                current_state.set_pause(true);
+                current_state.screen = Screen::Reader;
+                current_state.text_unsaved = loaded.adhoc;
+                current_state.index = Some(loaded.index);
+                current_state.cue_delays = (!loaded.meta.cues.is_empty()).then(|| cue_delays(&loaded.meta.cues));
+                current_state.text_name = loaded.name;
+                current_state.text_meta = loaded.meta;
+                current_state.text_content = loaded.content;
+                current_state.split_text();
+                current_state.run_search();
+                current_state.quiz_start = loaded.index;
+                current_state.quiz_words = 0;
+                current_state.set_pause(false);
+
+                let index_task = if !current_state.text_unsaved
+                && index_text(&mut current_state.library_index, &mut current_state.index_queue, &current_state.text_name, Some(&current_state.text_content)) {
+                    current_state.save_index()
+                } else {
+                    Task::none()
+                };
+
+                // Opened from a library search, the phrase is searched again in the
+                // filtered words and the nearest match to the indexed word is used
+                if let Some((target, phrase)) = current_state.pending_jump.take() {
+                    current_state.search.query = phrase;
+                    current_state.search.regex = false;
+                    current_state.run_search();
+                    let nearest = current_state.search.results.iter().enumerate()
+                    .min_by_key(|(_, result)| result.index.abs_diff(target))
+                    .map(|(i, _)| i);
+                    match nearest {
+                        Some(i) => current_state.jump_to_result(i),
+                        None => current_state.seek(target.min(current_state.get_text_len())),
+                    }
+                    current_state.sidebar = Some(Sidebar::Search);
+                    current_state.set_pause(true);
+                }
+                index_task
+            } else {
+                current_state.pending_jump = None;
+                current_state.notice = Some("The file couldn't be read".to_string());
                 Task::none()
-            },
-            ColorOption::Text => {
-                current_state.config.theme.text = rgb_to_hex(color);
-                current_state.display.text = false;
+            }
+        },
+        Message::ReadClipboard => {
+            iced::clipboard::read().map(Message::ClipboardRead)
+        },
+        Message::ClipboardRead(content) => {
+            match content {
+                Some(content) if !content.trim().is_empty() => {
+                    Task::done(Message::FileLoaded(Some(adhoc_text(content, "clipboard", "Clipboard"))))
+                },
+                _ => Task::none()
+            }
+        },
+        Message::SaveToLibrary => {
+            if current_state.text_unsaved {
                 let config_path = get_config_path();
+                // Two pastes within a second would otherwise share a name
+                current_state.text_name = unused_text_name(&config_path, &current_state.text_name);
+                save_text(&config_path, &current_state.text_name, &current_state.text_content, &current_state.text_meta);
+                current_state.text_unsaved = false;
+                if let Some(index) = current_state.index {
+                    current_state.config.text_history.insert(current_state.text_name.clone(), index);
+                }
                 save_config_file(config_path, &current_state.config);
-                Task::none()
-            },
-            ColorOption::Primary => {
-                current_state.config.theme.primary = rgb_to_hex(color);
-                current_state.display.primary = false;
+                if index_text(&mut current_state.library_index, &mut current_state.index_queue, &current_state.text_name, Some(&current_state.text_content)) {
+                    return current_state.save_index();
+                }
+            }
+            Task::none()
+        },
+        Message::FileHovered => {
+            current_state.file_hover = true;
+            Task::none()
+        },
+        Message::FileHoverLeft => {
+            current_state.file_hover = false;
+            Task::none()
+        },
+        Message::FileDropped(file_path) => {
+            current_state.file_hover = false;
+            let filename = file_path.file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+            let extension = file_path.extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+            let Ok(data) = std::fs::read(&file_path) else { return Task::none() };
+            let config_path = get_config_path();
+
+            if FONT_EXTENSIONS.contains(&extension.as_str()) {
+                if let Some((name, content)) = load_font_file(config_path, data) {
+                    current_state.pending_font = Some(name);
+                    return iced::font::load(content).map(Message::FontChanged)
+                }
+                current_state.notice = Some(format!("{} couldn't be read as a font", filename));
+            } else if is_archive(&filename) {
+                current_state.text_loader = true;
+                return Task::perform(archive_entries(data, filename), Message::ArchiveLoaded)
+            } else if is_readable(&filename) {
+                current_state.text_loader = true;
+                return Task::perform(file_processor(current_state.config.text_history.clone(), config_path, data, filename, current_state.config.preprocess()), Message::FileLoaded)
+            } else {
+                current_state.notice = Some(format!("{} isn't a supported file", filename));
+            }
+            Task::none()
+        },
+        Message::ShowLibrary => {
+            current_state.library = library_texts(&get_config_path());
+            current_state.screen = Screen::Library;
+            Task::none()
+        },
+        Message::ShowStatistics => {
+            // The running session is saved so it shows up too
+            current_state.save_session();
+            current_state.sessions = load_sessions(&get_config_path());
+            current_state.screen = Screen::Statistics;
+            Task::none()
+        },
+        Message::FinishSession => {
+            current_state.set_pause(true);
+            if let Some(session) = current_state.session.take() && session.words > 0 {
+                finish_session(&get_config_path(), &session);
+                let sessions = load_sessions(&get_config_path());
+                let training = current_state.config.training;
+                let baseline = training.enabled.then(|| (training.baseline, next_baseline(&training, &session)));
+                if let Some((_, next)) = baseline {
+                    current_state.config.training.baseline = next;
+                    let config_path = get_config_path();
+                    save_config_file(config_path, &current_state.config);
+                }
+                current_state.summary = Some(SessionSummary {
+                    session,
+                    today: today_total(&sessions),
+                    streak: reading_streaks(&sessions).0,
+                    baseline,
+                });
+                current_state.screen = Screen::Summary;
+            }
+            Task::none()
+        },
+        Message::QuizAnswered(i) => {
+            if current_state.quiz_choice.is_none() && let Some(quiz) = &current_state.quiz {
+                current_state.quiz_choice = Some(i);
+                if let Some(session) = &mut current_state.session {
+                    session.questions += 1;
+                    session.correct += usize::from(i == quiz.answer);
+                }
+                current_state.save_session();
+            }
+            Task::none()
+        },
+        Message::QuizContinue => {
+            current_state.quiz = None;
+            current_state.quiz_choice = None;
+            current_state.screen = Screen::Reader;
+            if current_state.index == Some(current_state.get_text_len()) {
+                return Task::done(Message::FinishSession);
+            }
+            current_state.set_pause(false);
+            Task::none()
+        },
+        Message::ToggleQuiz => {
+            current_state.config.quiz.enabled = !current_state.config.quiz.enabled;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::QuizIntervalChanged(interval) => {
+            if interval.is_empty() || interval.chars().all(|c| c.is_ascii_digit()) {
+                current_state.config.quiz.interval = interval.parse().unwrap_or(0);
                 let config_path = get_config_path();
                 save_config_file(config_path, &current_state.config);
-                Task::none()
-            },
-            ColorOption::_Success => {
-                current_state.config.theme.success = rgb_to_hex(color);
-                current_state.display.success = false;
+            }
+            Task::none()
+        },
+        Message::ToggleTraining => {
+            current_state.config.training.enabled = !current_state.config.training.enabled;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::EditTraining(edit) => {
+            let training = &mut current_state.config.training;
+            // Each field is capped so the training math stays in range
+            let (field, value, max) = match edit {
+                TrainingEdit::Baseline(value) => (&mut training.baseline, value, MAX_WPM),
+                TrainingEdit::Ramp(value) => (&mut training.ramp, value, MAX_WPM),
+                TrainingEdit::DrillInterval(value) => (&mut training.drill_interval, value, 3600),
+                TrainingEdit::DrillLength(value) => (&mut training.drill_length, value, 3600),
+                TrainingEdit::DrillBoost(value) => (&mut training.drill_boost, value, 500),
+            };
+            if value.is_empty() || value.chars().all(|c| c.is_ascii_digit()) {
+                *field = value.parse::<u32>().unwrap_or(if value.is_empty() { 0 } else { max }).min(max);
                 let config_path = get_config_path();
                 save_config_file(config_path, &current_state.config);
-                Task::none()
-            },
-            ColorOption::_Warning => {
-                current_state.config.theme.warning = rgb_to_hex(color);
-                current_state.display.warning = false;
+            }
+            Task::none()
+        },
+        Message::EditWarmup(edit) => {
+            let warmup = &mut current_state.config.warmup;
+            let (field, value) = match edit {
+                WarmupEdit::Words(value) => (&mut warmup.words, value),
+                WarmupEdit::StartSpeed(value) => (&mut warmup.start_speed, value),
+            };
+            if value.is_empty() || value.chars().all(|c| c.is_ascii_digit()) {
+                *field = value.parse().unwrap_or(0);
                 let config_path = get_config_path();
                 save_config_file(config_path, &current_state.config);
-                Task::none()
-            },
-            ColorOption::_Danger => {
-                current_state.config.theme.danger = rgb_to_hex(color);
-                current_state.display.danger = false;
+            }
+            Task::none()
+        },
+        Message::AutoPauseModeChanged(boundary, mode) => {
+            current_state.config.auto_pause.get_mut(boundary).mode = mode;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::AutoPauseDurationChanged(boundary, duration) => {
+            if duration.is_empty() || duration.chars().all(|c| c.is_ascii_digit()) {
+                current_state.config.auto_pause.get_mut(boundary).duration = duration.parse().unwrap_or(0);
                 let config_path = get_config_path();
                 save_config_file(config_path, &current_state.config);
-                Task::none()
-            },
+            }
+            Task::none()
         },
-        Message::FileDialog => {
-            if let Some((data, filename)) = open_file_dialog() {
-            
+        Message::KeyPressed(key, modifiers, repeat) => {
+            let Some(spec) = key_spec(&key, modifiers) else { return Task::none() };
+            if let Some(i) = current_state.rebinding {
+                if spec == "Escape" {
+                    current_state.rebinding = None;
+                    current_state.key_notice = None;
+                } else if let Some(action) = key_conflict(&current_state.config.keys, i, &spec) {
+                    current_state.key_notice = Some(format!("{} is already used by {}", spec, action));
+                } else if let Some(binding) = current_state.config.keys.get_mut(i) {
+                    binding.key = spec;
+                    current_state.rebinding = None;
+                    current_state.key_notice = None;
+                    let config_path = get_config_path();
+                    save_config_file(config_path, &current_state.config);
+                }
+                return Task::none();
+            }
+            // Other screens have their own controls, reading can't resume out of sight
+            let on_reader = current_state.screen == Screen::Reader;
+            let action = current_state.config.keys.iter()
+            .find(|binding| binding.key == spec)
+            .map(|binding| binding.action)
+            .filter(|action| on_reader || action.works_anywhere());
+            match action {
+                // Held keys only repeat actions meant to keep going
+                Some(action) if !repeat || action.repeats() => update(current_state, action_message(action)),
+                _ => Task::none(),
+            }
+        },
+        Message::ShowSettings => {
+            current_state.set_pause(true);
+            current_state.rebinding = None;
+            current_state.key_notice = None;
+            current_state.screen = Screen::Settings;
+            Task::none()
+        },
+        Message::VelocityChanged(velocity) => {
+            current_state.set_velocity(velocity);
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::ResetFont => {
+            current_state.config.font = "default".to_string();
+            current_state.current_font = iced::Font::DEFAULT;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::ClearTextHistory => {
+            current_state.config.text_history.clear();
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::ThemeSelected(name) => {
+            if let Some(theme) = current_state.get_themes().into_iter().find(|theme| theme.name == name) {
+                current_state.config.theme = theme.colors;
+                current_state.resolve_theme();
+                current_state.contrast_warnings.clear();
+                current_state.theme_name = name;
+                current_state.theme_notice = None;
                 let config_path = get_config_path();
+                save_config_file(config_path, &current_state.config);
+            }
+            Task::none()
+        },
+        Message::ThemeNameChanged(name) => {
+            current_state.theme_name = name;
+            Task::none()
+        },
+        Message::SaveTheme => {
+            let name = current_state.theme_name.trim().to_string();
+            if name.is_empty() {
+                return Task::none();
+            }
+            if theme_presets().iter().any(|theme| theme.name == name) {
+                current_state.theme_notice = Some(format!("{} is a built-in theme, pick another name", name));
+                return Task::none();
+            }
+            let theme = NamedTheme { name: name.clone(), colors: current_state.config.theme.clone() };
+            match current_state.config.themes.iter_mut().find(|saved| saved.name == name) {
+                Some(saved) => *saved = theme,
+                None => current_state.config.themes.push(theme),
+            }
+            current_state.theme_notice = Some(format!("Saved {}", name));
+            current_state.resolve_theme();
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::DeleteTheme(name) => {
+            current_state.config.themes.retain(|theme| theme.name != name);
+            current_state.theme_notice = None;
+            current_state.resolve_theme();
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::ImportTheme => {
+            let Some((data, filename)) = open_theme_dialog() else { return Task::none() };
+            match parse_theme_file(&data, &filename) {
+                Some(mut theme) => {
+                    // Imported themes never replace a preset
+                    if theme_presets().iter().any(|preset| preset.name == theme.name) {
+                        theme.name = format!("{} (imported)", theme.name);
+                    }
+                    current_state.config.theme = theme.colors.clone();
+                    current_state.contrast_warnings.clear();
+                    current_state.theme_notice = Some(format!("Imported {}", theme.name));
+                    current_state.theme_name = theme.name.clone();
+                    match current_state.config.themes.iter_mut().find(|saved| saved.name == theme.name) {
+                        Some(saved) => *saved = theme,
+                        None => current_state.config.themes.push(theme),
+                    }
+                    current_state.resolve_theme();
+                    let config_path = get_config_path();
+                    save_config_file(config_path, &current_state.config);
+                },
+                None => current_state.theme_notice = Some(format!("{} is not a valid theme file", filename)),
+            }
+            Task::none()
+        },
+        Message::ExportTheme => {
+            let name = current_state.get_current_theme_name()
+            .or_else(|| Some(current_state.theme_name.trim().to_string()).filter(|name| !name.is_empty()))
+            .unwrap_or_else(|| "Custom".to_string());
+            let theme = NamedTheme { name: name.clone(), colors: current_state.config.theme.clone() };
+            if let Err(error) = save_file_dialog(&name, "toml", theme_file(&theme)) {
+                current_state.notice = Some(format!("The theme couldn't be exported: {}", error));
+            }
+            Task::none()
+        },
+        Message::ThemeModeChanged(mode) => {
+            current_state.config.auto_theme.mode = mode;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            current_state.apply_auto_theme();
+            if mode == ThemeMode::System {
+                system::theme().map(Message::SystemThemeChanged)
+            } else {
+                Task::none()
+            }
+        },
+        Message::AutoLightThemeChanged(name) => {
+            current_state.config.auto_theme.light = name;
+            current_state.apply_auto_theme();
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::AutoDarkThemeChanged(name) => {
+            current_state.config.auto_theme.dark = name;
+            current_state.apply_auto_theme();
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::LightFromChanged(time) => {
+            current_state.config.auto_theme.light_from = time;
+            current_state.apply_auto_theme();
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::DarkFromChanged(time) => {
+            current_state.config.auto_theme.dark_from = time;
+            current_state.apply_auto_theme();
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::SystemThemeChanged(mode) => {
+            current_state.system_mode = Some(mode);
+            current_state.apply_auto_theme();
+            Task::none()
+        },
+        Message::ThemeTick => {
+            current_state.apply_auto_theme();
+            Task::none()
+        },
+        Message::ShowKeys => {
+            current_state.screen = Screen::Keys;
+            Task::none()
+        },
+        Message::RebindKey(i) => {
+            current_state.rebinding = Some(i);
+            current_state.key_notice = None;
+            Task::none()
+        },
+        Message::RestoreKeyBindings => {
+            current_state.config.keys = default_key_bindings();
+            current_state.rebinding = None;
+            current_state.key_notice = None;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::WordScrolled(delta) => {
+            let (x, y) = match delta {
+                ScrollDelta::Lines { x, y } => (x, y),
+                ScrollDelta::Pixels { x, y } => (x / SWIPE_STEP, y / SCROLL_STEP),
+            };
+            current_state.scroll_offset.0 += x;
+            current_state.scroll_offset.1 += y;
+            // Whole steps are taken now, the rest adds up with the next scroll
+            let (sentences, words) = (current_state.scroll_offset.0.trunc(), current_state.scroll_offset.1.trunc());
+            current_state.scroll_offset.0 -= sentences;
+            current_state.scroll_offset.1 -= words;
 
-                // Running off the main thread
-                current_state.text_loader = true;
-                return Task::perform(file_processor(current_state.config.text_history.clone(), config_path, data, filename), Message::FileLoaded)
+            let sentence = if sentences > 0.0 { Message::PreviousSentence } else { Message::NextSentence };
+            let word = match (current_state.config.scroll, words > 0.0) {
+                (ScrollMode::Words, true) => Message::ArrowLeftPressed,
+                (ScrollMode::Words, false) => Message::ArrowRightPressed,
+                (ScrollMode::Speed, true) => Message::ArrowUpPressed,
+                (ScrollMode::Speed, false) => Message::ArrowDownPressed,
+            };
+            let mut tasks: Vec<Task<Message>> = Vec::new();
+            for _ in 0..sentences.abs() as usize {
+                tasks.push(update(current_state, sentence.clone()));
+            }
+            for _ in 0..words.abs() as usize {
+                tasks.push(update(current_state, word.clone()));
             }
+            Task::batch(tasks)
+        },
+        Message::ScrollModeChanged(mode) => {
+            current_state.config.scroll = mode;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
             Task::none()
         },
-        Message::FileLoaded(text) => {
-            current_state.text_loader = false;
-            if let Some((content, title, index)) = text {
-                // 1. Split text string without counting white spaces
-                // 2. Convert each &str slice to String, then to Box<str>
-                // 3. Collects into a Vec
-                current_state.text = Some(content
-                    .split_whitespace()
-                    .map(|s: &str| s.to_string().into_boxed_str())
-                    .collect());
-                
-                current_state.index = Some(index);
-                current_state.pause = false;
-                current_state.text_title = title;
+        Message::PreviousSentence => {
+            if let Some(text) = &current_state.text && let Some(idx) = current_state.index && idx > 0 {
+                let (start, _) = sentence_bounds(text, idx.min(text.len().saturating_sub(1)));
+                // Right after a sentence start, go back to the one before
+                let start = if idx - start < 2 && start > 0 { sentence_bounds(text, start - 1).0 } else { start };
+                current_state.seek(start);
+                if let Some(session) = &mut current_state.session {
+                    session.rewinds += 1;
+                }
+            }
+            Task::none()
+        },
+        Message::NextSentence => {
+            if let Some(text) = &current_state.text && let Some(idx) = current_state.index && idx < text.len() {
+                let (_, end) = sentence_bounds(text, idx);
+                current_state.seek(end.min(text.len() - 1));
             }
             Task::none()
         },
+        Message::MouseMoved => {
+            current_state.controls_moved = Some(time::Instant::now());
+            Task::none()
+        },
+        Message::ControlsHovered(hovered) => {
+            current_state.controls_hovered = hovered;
+            Task::none()
+        },
+        Message::GoalKindChanged(kind) => {
+            current_state.config.goal.kind = kind;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::GoalAmountChanged(amount) => {
+            if amount.is_empty() || amount.chars().all(|c| c.is_ascii_digit()) {
+                current_state.config.goal.amount = amount.parse().unwrap_or(0);
+                let config_path = get_config_path();
+                save_config_file(config_path, &current_state.config);
+            }
+            Task::none()
+        },
+        Message::ShowStripped => {
+            current_state.screen = Screen::Stripped;
+            Task::none()
+        },
+        Message::ToggleStripBoilerplate => {
+            current_state.config.strip_boilerplate = !current_state.config.strip_boilerplate;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::ToggleStripBackMatter => {
+            current_state.config.strip_back_matter = !current_state.config.strip_back_matter;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::ShowFilters => {
+            current_state.set_pause(true);
+            current_state.screen = Screen::Filters;
+            Task::none()
+        },
+        Message::EditFilter(i, edit) => {
+            if let Some(rule) = current_state.config.filters.get_mut(i) {
+                match edit {
+                    FilterEdit::Name(name) => rule.name = name,
+                    FilterEdit::Pattern(pattern) => rule.pattern = pattern,
+                    FilterEdit::Replace(replace) => rule.replace = replace,
+                }
+            }
+            Task::none()
+        },
+        Message::ToggleFilter(i) => {
+            if let Some(rule) = current_state.config.filters.get_mut(i) {
+                rule.enabled = !rule.enabled;
+            }
+            Task::none()
+        },
+        Message::ToggleTextFilter(i) => {
+            if let Some(rule) = current_state.config.filters.get(i) {
+                let disabled = &mut current_state.text_meta.disabled_filters;
+                if let Some(position) = disabled.iter().position(|id| *id == rule.id) {
+                    disabled.remove(position);
+                } else {
+                    disabled.push(rule.id.clone());
+                }
+                current_state.save_meta();
+            }
+            Task::none()
+        },
+        Message::AddFilter => {
+            current_state.config.filters.push(FilterRule {
+                id: new_filter_id(&current_state.config.filters),
+                name: format!("Rule {}", current_state.config.filters.len() + 1),
+                pattern: String::new(),
+                replace: String::new(),
+                enabled: true,
+            });
+            Task::none()
+        },
+        Message::RemoveFilter(i) => {
+            if i < current_state.config.filters.len() {
+                current_state.config.filters.remove(i);
+            }
+            Task::none()
+        },
+        Message::RestoreFilterPresets => {
+            for preset in filter_presets() {
+                if !current_state.config.filters.iter().any(|rule| rule.id == preset.id) {
+                    current_state.config.filters.push(preset);
+                }
+            }
+            Task::none()
+        },
+        Message::ApplyFilters => {
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            if current_state.text.is_some() {
+                current_state.split_text();
+                current_state.run_search();
+            }
+            current_state.screen = Screen::Reader;
+            Task::none()
+        },
+        Message::ToggleSidebar(sidebar) => {
+            // Opened from the settings screen, the sidebar shows next to the reader
+            if current_state.screen != Screen::Reader {
+                current_state.rebinding = None;
+                current_state.key_notice = None;
+                current_state.screen = Screen::Reader;
+                current_state.sidebar = Some(sidebar);
+            } else {
+                current_state.sidebar = if current_state.sidebar == Some(sidebar) { None } else { Some(sidebar) };
+            }
+            Task::none()
+        },
+        Message::BookmarkLabelChanged(label) => {
+            current_state.bookmark_label = label;
+            Task::none()
+        },
+        Message::AddBookmark => {
+            if let (Some(text), Some(index)) = (&current_state.text, current_state.index) && index < text.len() {
+                // Without a label the bookmark is named after its word
+                let label = if current_state.bookmark_label.trim().is_empty() {
+                    text[index].to_string()
+                } else {
+                    std::mem::take(&mut current_state.bookmark_label)
+                };
+                current_state.text_meta.bookmarks.push(Bookmark { label, index });
+                current_state.text_meta.bookmarks.sort_by_key(|bookmark| bookmark.index);
+                current_state.save_meta();
+            }
+            Task::none()
+        },
+        Message::JumpToBookmark(i) => {
+            if let Some(bookmark) = current_state.text_meta.bookmarks.get(i) {
+                current_state.seek(bookmark.index.min(current_state.get_text_len()));
+            }
+            Task::none()
+        },
+        Message::JumpToChapter(i) => {
+            if let Some(start) = current_state.toc_starts.get(i).copied() {
+                current_state.seek(start);
+            }
+            Task::none()
+        },
+        Message::RemoveBookmark(i) => {
+            if i < current_state.text_meta.bookmarks.len() {
+                current_state.text_meta.bookmarks.remove(i);
+                current_state.save_meta();
+            }
+            Task::none()
+        },
+        Message::AddHighlight => {
+            if let (Some(text), Some(index)) = (&current_state.text, current_state.index) && index < text.len() {
+                let (start, end) = sentence_bounds(text, index);
+                // Pressing twice in the same sentence keeps a single highlight
+                if !current_state.text_meta.highlights.iter().any(|h| h.start == start && h.end == end) {
+                    current_state.text_meta.highlights.push(Highlight {
+                        start,
+                        end,
+                        text: text[start..end].join(" "),
+                        note: String::new(),
+                    });
+                    current_state.text_meta.highlights.sort_by_key(|h| h.start);
+                    current_state.save_meta();
+                }
+            }
+            Task::none()
+        },
+        Message::ShowHighlights => {
+            current_state.set_pause(true);
+            current_state.screen = Screen::Highlights;
+            Task::none()
+        },
+        Message::HighlightNoteChanged(i, note) => {
+            if let Some(highlight) = current_state.text_meta.highlights.get_mut(i) {
+                highlight.note = note;
+            }
+            Task::none()
+        },
+        Message::SaveHighlights => {
+            current_state.save_meta();
+            current_state.screen = Screen::Reader;
+            Task::none()
+        },
+        Message::JumpToHighlight(i) => {
+            // Notes typed before jumping are kept
+            current_state.save_meta();
+            if let Some(highlight) = current_state.text_meta.highlights.get(i) {
+                current_state.seek(highlight.start.min(current_state.get_text_len()));
+                current_state.screen = Screen::Reader;
+            }
+            Task::none()
+        },
+        Message::RemoveHighlight(i) => {
+            if i < current_state.text_meta.highlights.len() {
+                current_state.text_meta.highlights.remove(i);
+                current_state.save_meta();
+            }
+            Task::none()
+        },
+        Message::ExportHighlights(format) => {
+            let title = current_state.get_title();
+            let highlights = &current_state.text_meta.highlights;
+            let exported = match format {
+                ExportFormat::Markdown => save_file_dialog(&current_state.text_name, "md", highlights_to_markdown(title, highlights)),
+                ExportFormat::Json => save_file_dialog(&current_state.text_name, "json", highlights_to_json(title, highlights)),
+            };
+            if let Err(error) = exported {
+                current_state.notice = Some(format!("The highlights couldn't be exported: {}", error));
+            }
+            Task::none()
+        },
+        Message::OpenSearch => {
+            current_state.sidebar = Some(Sidebar::Search);
+            iced::widget::operation::focus(SEARCH_INPUT)
+        },
+        Message::SearchQueryChanged(query) => {
+            current_state.search.query = query;
+            current_state.run_search();
+            Task::none()
+        },
+        Message::ToggleSearchRegex => {
+            current_state.search.regex = !current_state.search.regex;
+            current_state.run_search();
+            Task::none()
+        },
+        Message::ToggleSearchCase => {
+            current_state.search.case_insensitive = !current_state.search.case_insensitive;
+            current_state.run_search();
+            Task::none()
+        },
+        Message::SearchNext => {
+            // Without a selection the first result after the current word is used
+            let current = current_state.index.unwrap_or(0);
+            let next = match current_state.search.selected {
+                Some(i) => (i + 1) % current_state.search.results.len().max(1),
+                None => current_state.search.results.iter().position(|r| r.index > current).unwrap_or(0),
+            };
+            current_state.jump_to_result(next);
+            Task::none()
+        },
+        Message::SearchPrevious => {
+            let current = current_state.index.unwrap_or(0);
+            let count = current_state.search.results.len();
+            let previous = match current_state.search.selected {
+                Some(i) => (i + count.max(1) - 1) % count.max(1),
+                None => current_state.search.results.iter().rposition(|r| r.index < current).unwrap_or(count.saturating_sub(1)),
+            };
+            current_state.jump_to_result(previous);
+            Task::none()
+        },
+        Message::JumpToResult(i) => {
+            current_state.jump_to_result(i);
+            Task::none()
+        },
+        Message::ShowReader => {
+            current_state.rebinding = None;
+            current_state.key_notice = None;
+            current_state.screen = Screen::Reader;
+            Task::none()
+        },
+        Message::OpenLibraryText(name) => {
+            // Cached texts are found by name, no file data needed
+            let config_path = get_config_path();
+            current_state.text_loader = true;
+            Task::perform(file_processor(current_state.config.text_history.clone(), config_path, Vec::new(), format!("{}.txt", name), current_state.config.preprocess()), Message::FileLoaded)
+        },
+        Message::IndexLoaded(index) => {
+            current_state.library_index = Some(std::sync::Arc::new(index));
+            let mut added = false;
+            for name in std::mem::take(&mut current_state.index_queue) {
+                added |= index_text(&mut current_state.library_index, &mut current_state.index_queue, &name, None);
+            }
+            if added { current_state.save_index() } else { Task::none() }
+        },
+        Message::IndexSaved => {
+            current_state.index_saving = false;
+            if current_state.index_dirty { current_state.save_index() } else { Task::none() }
+        },
+        Message::LibraryQueryChanged(query) => {
+            if query.trim().is_empty() {
+                current_state.library_results.clear();
+            }
+            current_state.library_query = query;
+            Task::none()
+        },
+        Message::SearchLibrary => {
+            match &current_state.library_index {
+                Some(index) => Task::perform(search_library(get_config_path(), index.clone(), current_state.library_query.clone()), |(query, results)| Message::LibrarySearched(query, results)),
+                None => Task::none(),
+            }
+        },
+        Message::LibrarySearched(query, results) => {
+            // Results of a query edited since are dropped
+            if query == current_state.library_query {
+                current_state.library_results = results;
+            }
+            Task::none()
+        },
+        Message::OpenLibraryResult(i) => {
+            if let Some(result) = current_state.library_results.get(i) {
+                current_state.pending_jump = Some((result.index, current_state.library_query.clone()));
+                return Task::done(Message::OpenLibraryText(result.name.clone()))
+            }
+            Task::none()
+        },
+        Message::SetInbox => {
+            if let Some(folder) = open_folder_dialog() {
+                current_state.config.inbox = Some(folder);
+                current_state.inbox_pending.clear();
+                let config_path = get_config_path();
+                save_config_file(config_path, &current_state.config);
+            }
+            Task::none()
+        },
+        Message::ClearInbox => {
+            current_state.config.inbox = None;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
+        Message::InboxTick => {
+            let Some(inbox) = &current_state.config.inbox else { return Task::none() };
+            Task::perform(scan_inbox(inbox.clone()), |(inbox, files)| Message::InboxScanned(inbox, files))
+        },
+        Message::InboxScanned(inbox, files) => {
+            // The folder was changed or cleared while it was being looked at
+            if current_state.config.inbox.as_deref() != Some(inbox.as_str()) {
+                return Task::none();
+            }
+            let mut ready: Vec<(std::path::PathBuf, FileStamp)> = Vec::new();
+            for (file_path, stamp) in files {
+                if current_state.inbox_importing.contains(&file_path) || current_state.inbox_seen.get(&file_path) == Some(&stamp) {
+                    continue;
+                }
+                // Imported once it looks the same on two scans in a row
+                if current_state.inbox_pending.insert(file_path.clone(), stamp) == Some(stamp) {
+                    current_state.inbox_pending.remove(&file_path);
+                    current_state.inbox_importing.insert(file_path.clone());
+                    ready.push((file_path, stamp));
+                }
+            }
+
+            // Extracted in the background, the reader is left untouched
+            let preprocess = current_state.config.preprocess();
+            Task::batch(ready.into_iter().map(|(file_path, stamp)| {
+                Task::perform(import_file(get_config_path(), file_path.clone(), preprocess), move |names| {
+                    Message::InboxImported(file_path.clone(), stamp, names)
+                })
+            }))
+        },
+        Message::InboxImported(file_path, stamp, names) => {
+            current_state.inbox_importing.remove(&file_path);
+            // Unreadable files are tried again, the others only once they change
+            let Some(names) = names else { return Task::none() };
+            current_state.inbox_seen.insert(file_path, stamp);
+            save_inbox_seen(&get_config_path(), &current_state.inbox_seen);
+            let mut added = false;
+            for name in &names {
+                added |= index_text(&mut current_state.library_index, &mut current_state.index_queue, name, None);
+            }
+            if !names.is_empty() {
+                current_state.library = library_texts(&get_config_path());
+            }
+            if added { current_state.save_index() } else { Task::none() }
+        },
         Message::Tick => {
+            // Waits on the last word before a boundary until reading is resumed
+            if !current_state.pause && current_state.boundary_wait != current_state.index
+                && current_state.get_boundary_pause().is_some_and(|pause| pause.mode == PauseMode::WaitForKey) {
+                current_state.boundary_wait = current_state.index;
+                current_state.set_pause(true);
+                return Task::none();
+            }
             if let (Some(text), Some(index)) = (&current_state.text, &current_state.index) 
                 && *index < text.len() && !current_state.pause {
                 let current_instant = time::Instant::now();
@@ -241,19 +1653,47 @@ pub fn update(current_state: &mut State, message: Message) -> Task<Message> {
             && !current_state.text_animation.is_animating(instant)
 
             && current_state.text_animation.value() == 0.0 {
-                current_state.index = Some(*index + 1);
+                let next = *index + 1;
+                current_state.index = Some(next);
                 current_state.text_animation.go_mut(1.0, instant);
+
+                if let Some(session) = &mut current_state.session {
+                    session.words += 1;
+                }
+                if current_state.session.as_ref().is_some_and(|s| s.words.is_multiple_of(SESSION_SAVE_WORDS)) {
+                    current_state.save_session();
+                }
+                if current_state.toc_starts.contains(&next) {
+                    current_state.warmup_from = Some(next);
+                }
+                current_state.quiz_words += 1;
+                if current_state.quiz_due(next) {
+                    current_state.start_quiz(next);
+                }
+                // Reaching the end of the text ends the session, after the last question
+                if next == current_state.get_text_len() && current_state.quiz.is_none() {
+                    return Task::done(Message::FinishSession);
+                }
             }
             Task::none()
         },
         Message::ArrowDownPressed => {
-            current_state.set_velocity((current_state.get_velocity() + 100).clamp(200, 2000));
+            // While training the arrows move the baseline instead
+            if current_state.config.training.enabled {
+                current_state.config.training.baseline = current_state.config.training.baseline.saturating_sub(10).max(MIN_WPM);
+            } else {
+                current_state.set_velocity((current_state.get_velocity() + 100).clamp(200, 2000));
+            }
             let config_path = get_config_path();
             save_config_file(config_path, &current_state.config);
             Task::none()
         },
         Message::ArrowUpPressed => {
-            current_state.set_velocity((current_state.get_velocity() - 100).clamp(200, 2000));
+            if current_state.config.training.enabled {
+                current_state.config.training.baseline = current_state.config.training.baseline.saturating_add(10).min(MAX_WPM);
+            } else {
+                current_state.set_velocity((current_state.get_velocity() - 100).clamp(200, 2000));
+            }
             let config_path = get_config_path();
             save_config_file(config_path, &current_state.config);
             Task::none()
@@ -262,29 +1702,32 @@ pub fn update(current_state: &mut State, message: Message) -> Task<Message> {
             if let Some(_text) = &current_state.text && let Some(mut idx) = current_state.index
             && idx > 0 {
                 idx -= 1;
-                current_state.index = Some(idx);
+                current_state.seek(idx);
+                if let Some(session) = &mut current_state.session {
+                    session.rewinds += 1;
+                }
             }
           Task::none()
         },
         Message::ArrowRightPressed => {
             if let Some(text) = &current_state.text && let Some(mut idx) = current_state.index
-            && idx < text.len() - 1 {
+            && idx + 1 < text.len() {
                 idx += 1;
-                current_state.index = Some(idx);
+                current_state.seek(idx);
             }
           Task::none()
         },
         Message::SpacePressed => {
-                current_state.pause = !current_state.pause;
-                if let Some(index) = current_state.index {
+                current_state.set_pause(!current_state.pause);
+                if let Some(index) = current_state.index && !current_state.text_unsaved {
                     let config_path = get_config_path();
-                    current_state.config.text_history.insert(current_state.text_title.clone(), index);
+                    current_state.config.text_history.insert(current_state.text_name.clone(), index);
                     save_config_file(config_path, &current_state.config);
                 }
                 Task::none()
             },
         Message::ResetIndex => {
-          current_state.index = Some(0);
+          current_state.seek(0);
           Task::none()
         },
         Message::ToggleFullscreen(key) => match key {
@@ -308,6 +1751,12 @@ pub fn update(current_state: &mut State, message: Message) -> Task<Message> {
                  }
              },
         },
+        Message::ToggleSubtitleTiming => {
+            current_state.config.subtitle_timing = !current_state.config.subtitle_timing;
+            let config_path = get_config_path();
+            save_config_file(config_path, &current_state.config);
+            Task::none()
+        },
         Message::TextSizeChanged(size) => {
             current_state.set_text_size(size);
             let config_path = get_config_path();
@@ -315,58 +1764,185 @@ pub fn update(current_state: &mut State, message: Message) -> Task<Message> {
             Task::none()
         },
         Message::FileDialogFont => {
+            let Some(data) = open_file_dialog_font() else { return Task::none() };
             let config_path = get_config_path();
-            if let Some((name, content)) = open_file_dialog_font(config_path) {
-                current_state.config.font = name;
-                
+            if let Some((name, content)) = load_font_file(config_path, data) {
+                current_state.pending_font = Some(name);
                 iced::font::load(content)
                 .map(Message::FontChanged)
-                
             } else {
+                current_state.notice = Some("The font file couldn't be read, only TTF and OTF fonts are supported".to_string());
                 Task::none()
             }
         },
         Message::FontChanged(result) => {
             match result {
                 Ok(()) => {
-                    let config_path = get_config_path();
-                    save_config_file(config_path, &current_state.config);
+                    if let Some(name) = current_state.pending_font.take() {
+                        current_state.config.font = name;
+                        let config_path = get_config_path();
+                        save_config_file(config_path, &current_state.config);
+                    }
                     current_state.current_font = iced::Font::with_name(current_state.config.font.clone().leak())
                 },
-                Err(e) => panic!("Error updating font: {:?}", e),
+                Err(_) => {
+                    current_state.pending_font = None;
+                    current_state.notice = Some("The font couldn't be loaded".to_string());
+                },
             }
             Task::none()
+        },
+        Message::DismissNotice => {
+            current_state.notice = None;
+            Task::none()
+        }
+    }
+}
+
+// Keeps the library index in sync with the cache, texts are read from it when no content is given.
+// Texts are queued while the index loads, returns whether the index changed and needs saving
+fn index_text(library_index: &mut Option<std::sync::Arc<LibraryIndex>>, queue: &mut Vec<String>, name: &str, content: Option<&str>) -> bool {
+    let Some(index) = library_index else {
+        queue.push(name.to_string());
+        return false;
+    };
+    if index.contains(name) {
+        return false;
+    }
+    // Copied only when a save still holds the previous version
+    let index = std::sync::Arc::make_mut(index);
+    match content {
+        Some(content) => index.add_text(name, content),
+        None => {
+            let content = std::fs::read_to_string(format!("{}texts/{}.txt", get_config_path().get_data(), name)).unwrap_or_default();
+            index.add_text(name, &content);
         }
     }
+    true
+}
+
+// Word range of the sentence holding the word at index, end excluded
+fn sentence_bounds(text: &[Box<str>], index: usize) -> (usize, usize) {
+    let ends_sentence = |word: &str| word.trim_end_matches(['"', '\'', '”', '’', ')']).ends_with(['.', '!', '?']);
+    let start = text[..index].iter()
+    .rposition(|word| ends_sentence(word))
+    .map(|i| i + 1)
+    .unwrap_or(0);
+    let end = text[index..].iter()
+    .position(|word| ends_sentence(word))
+    .map(|i| index + i + 1)
+    .unwrap_or(text.len());
+    (start, end)
+}
+
+// Spreads each cue evenly over its words so the last one ends at the cue end time,
+// gaps between cues are absorbed by the following cue
+fn cue_delays(cues: &[Cue]) -> Vec<u64> {
+    let mut delays: Vec<u64> = Vec::new();
+    let mut previous_end = cues.first().map(|c| c.start).unwrap_or(0);
+    for cue in cues {
+        let span = cue.end.saturating_sub(previous_end);
+        let delay = (span / cue.words.max(1) as u64).max(50);
+        delays.extend(std::iter::repeat_n(delay, cue.words));
+        previous_end = cue.end;
+    }
+    delays
+}
+
+// Checks every color against the background
+fn contrast_warnings(theme: &ColorTheme) -> Vec<ContrastWarning> {
+    let background = hex_to_rgb(&theme.background);
+    let colors = [
+        (ColorOption::Text, &theme.text),
+        (ColorOption::Primary, &theme.primary),
+        (ColorOption::Success, &theme.success),
+        (ColorOption::Warning, &theme.warning),
+        (ColorOption::Danger, &theme.danger),
+    ];
+    colors.into_iter()
+    .filter_map(|(option, hex)| {
+        let ratio = contrast_ratio(hex_to_rgb(hex), background);
+        let (level, target) = if ratio < AA_CONTRAST { ("AA", AA_CONTRAST) } else if ratio < AAA_CONTRAST { ("AAA", AAA_CONTRAST) } else { return None };
+        let (suggestion, reached) = nearest_contrast(hex_to_rgb(hex), background, target);
+        Some(ContrastWarning { option, ratio, level, target, suggestion, reached })
+    })
+    .collect()
+}
+
+fn action_message(action: Action) -> Message {
+    match action {
+        Action::PlayPause => Message::SpacePressed,
+        Action::PreviousWord => Message::ArrowLeftPressed,
+        Action::NextWord => Message::ArrowRightPressed,
+        Action::Faster => Message::ArrowUpPressed,
+        Action::Slower => Message::ArrowDownPressed,
+        Action::ToggleFullscreen => Message::ToggleFullscreen(FullscreenKey::F11),
+        Action::ExitFullscreen => Message::ToggleFullscreen(FullscreenKey::Escape),
+        Action::ReadClipboard => Message::ReadClipboard,
+        Action::Search => Message::OpenSearch,
+        Action::AddBookmark => Message::AddBookmark,
+        Action::AddHighlight => Message::AddHighlight,
+    }
 }
 
 pub fn subscription(current_state: &State) -> Subscription<Message> {
-    let timer_subscription = time::every(std::time::Duration::from_millis(current_state.get_velocity().into()))
+    let timer_subscription = time::every(std::time::Duration::from_millis(current_state.get_word_delay()))
     .map(|_| Message::Tick);
 
     let keyboard_listener = keyboard::listen()
         .filter_map(|e: Event| {
             match e {
-                Event::KeyPressed {key, ..} => match key {
-                    Key::Named(Named::ArrowDown) => Some(Message::ArrowDownPressed),
-                    Key::Named(Named::ArrowUp) => Some(Message::ArrowUpPressed),
-                    Key::Named(Named::ArrowLeft) => Some(Message::ArrowLeftPressed),
-                    Key::Named(Named::ArrowRight) => Some(Message::ArrowRightPressed),
-                    Key::Named(Named::Space) => Some(Message::SpacePressed),
-                    Key::Named(Named::F11) => Some(Message::ToggleFullscreen(FullscreenKey::F11)),
-                    Key::Named(Named::Escape) => Some(Message::ToggleFullscreen(FullscreenKey::Escape)),
-                    _ => None
-                },
+                Event::KeyPressed {key, modifiers, repeat, ..} => Some(Message::KeyPressed(key, modifiers, repeat)),
                 _ => None
             }
         });
 
+    let file_drop_listener = event::listen_with(|e, _status, _id| {
+        match e {
+            iced::Event::Window(window::Event::FileHovered(_)) => Some(Message::FileHovered),
+            iced::Event::Window(window::Event::FilesHoveredLeft) => Some(Message::FileHoverLeft),
+            iced::Event::Window(window::Event::FileDropped(file_path)) => Some(Message::FileDropped(file_path)),
+            _ => None
+        }
+    });
+
     let frame_lsitener = window::frames()
     .map(Message::TextAnimation);
+
+    let theme_subscription = match current_state.get_auto_theme().mode {
+        ThemeMode::System => system::theme_changes().map(Message::SystemThemeChanged),
+        ThemeMode::Schedule => time::every(std::time::Duration::from_secs(60)).map(|_| Message::ThemeTick),
+        ThemeMode::Manual => Subscription::none(),
+    };
+
+    let inbox_subscription = if current_state.get_inbox().is_some() {
+        time::every(std::time::Duration::from_secs(3))
+        .map(|_| Message::InboxTick)
+    } else {
+        Subscription::none()
+    };
         
     Subscription::batch([
         timer_subscription,
         keyboard_listener,
-        frame_lsitener
+        file_drop_listener,
+        frame_lsitener,
+        inbox_subscription,
+        theme_subscription,
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sentence_bounds_stop_at_sentence_ends() {
+        let text: Vec<Box<str>> = "He left. “Why?” she asked. Nobody knew"
+        .split_whitespace().map(Into::into).collect();
+        assert_eq!(sentence_bounds(&text, 0), (0, 2));
+        assert_eq!(sentence_bounds(&text, 2), (2, 3));
+        assert_eq!(sentence_bounds(&text, 4), (3, 5));
+        assert_eq!(sentence_bounds(&text, 6), (5, 7));
+    }
+}
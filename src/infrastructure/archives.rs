@@ -0,0 +1,140 @@
+use std::io::Read;
+use crate::model::ArchiveEntry;
+use super::config::TEXT_EXTENSIONS;
+
+const COMPRESSED_EXTENSIONS: [&str; 4] = ["gz", "bz2", "xz", "tgz"];
+pub const ARCHIVE_EXTENSIONS: [&str; 6] = ["zip", "tar", "tgz", "gz", "bz2", "xz"];
+// Largest decompressed file or archive accepted, so a small bomb can't fill the memory
+const MAX_DECOMPRESSED: u64 = 256 * 1024 * 1024;
+
+// Reads up to the limit, None when there is more
+fn read_limited(reader: impl Read, limit: u64) -> Option<Vec<u8>> {
+    let mut result: Vec<u8> = Vec::new();
+    reader.take(limit + 1).read_to_end(&mut result).ok()?;
+    (result.len() as u64 <= limit).then_some(result)
+}
+
+// Output of the xz decoder, which writes instead of being read, failing past the limit
+struct LimitedWriter(Vec<u8>);
+
+impl std::io::Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if (self.0.len() + buf.len()) as u64 > MAX_DECOMPRESSED {
+            return Err(std::io::Error::other("decompressed data is too large"));
+        }
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// Unwraps gzip, bzip2 and xz layers, "notes.txt.gz" becomes "notes.txt"
+pub fn decompress(mut data: Vec<u8>, mut filename: String) -> Option<(Vec<u8>, String)> {
+    loop {
+        let Some((stem, ext)) = filename.rsplit_once('.') else { return Some((data, filename)) };
+        let ext = ext.to_lowercase();
+        let result = match ext.as_str() {
+            "gz" | "tgz" => read_limited(flate2::read::MultiGzDecoder::new(&data[..]), MAX_DECOMPRESSED)?,
+            "bz2" => read_limited(bzip2::read::MultiBzDecoder::new(&data[..]), MAX_DECOMPRESSED)?,
+            "xz" => {
+                let mut output = LimitedWriter(Vec::new());
+                lzma_rs::xz_decompress(&mut &data[..], &mut output).ok()?;
+                output.0
+            },
+            _ => return Some((data, filename))
+        };
+        filename = if ext == "tgz" { format!("{}.tar", stem) } else { stem.to_string() };
+        data = result;
+    }
+}
+
+// Lowercase extension left once compression layers are removed
+fn inner_extension(filename: &str) -> Option<String> {
+    let filename = filename.to_lowercase();
+    let mut name = filename.as_str();
+    while let Some((stem, ext)) = name.rsplit_once('.') {
+        if ext == "tgz" {
+            return Some("tar".to_string());
+        }
+        if !COMPRESSED_EXTENSIONS.contains(&ext) {
+            return Some(ext.to_string());
+        }
+        name = stem;
+    }
+    None
+}
+
+pub fn is_archive(filename: &str) -> bool {
+    inner_extension(filename).is_some_and(|ext| ext == "zip" || ext == "tar")
+}
+
+pub fn is_readable(filename: &str) -> bool {
+    inner_extension(filename).is_some_and(|ext| TEXT_EXTENSIONS.contains(&ext.as_str()))
+}
+
+// Lists every readable file of a zip or tar archive, compressed or not,
+// as long as their sizes add up to less than the decompression limit
+pub async fn archive_entries(data: Vec<u8>, filename: String) -> Vec<ArchiveEntry> {
+    let Some((data, filename)) = decompress(data, filename) else { return Vec::new() };
+    let mut entries: Vec<ArchiveEntry> = Vec::new();
+    let mut left = MAX_DECOMPRESSED;
+
+    if filename.to_lowercase().ends_with(".zip") {
+        let Ok(mut archive) = zip::ZipArchive::new(std::io::Cursor::new(data)) else { return entries };
+        for i in 0..archive.len() {
+            let Ok(mut file) = archive.by_index(i) else { continue };
+            if file.is_dir() || !is_readable(file.name()) {
+                continue;
+            }
+            let name = file.name().to_string();
+            let Some(content) = read_limited(&mut file, left) else { continue };
+            left -= content.len() as u64;
+            entries.push(ArchiveEntry { name, data: content });
+        }
+    } else {
+        let mut archive = tar::Archive::new(&data[..]);
+        let Ok(files) = archive.entries() else { return entries };
+        for mut file in files.flatten() {
+            let Ok(path) = file.path() else { continue };
+            let name = path.to_string_lossy().to_string();
+            if !file.header().entry_type().is_file() || !is_readable(&name) {
+                continue;
+            }
+            let Some(content) = read_limited(&mut file, left) else { continue };
+            left -= content.len() as u64;
+            entries.push(ArchiveEntry { name, data: content });
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extensions_ignore_case() {
+        assert!(is_readable("Book.PDF"));
+        assert!(is_readable("notes.TXT.GZ"));
+        assert!(is_archive("X.ZIP"));
+        assert!(is_archive("backup.TGZ"));
+        assert!(!is_readable("x.gz"));
+        assert!(!is_archive("Book.PDF"));
+    }
+
+    #[test]
+    fn reads_stop_at_the_limit() {
+        assert_eq!(read_limited(&b"abcdef"[..], 6), Some(b"abcdef".to_vec()));
+        assert_eq!(read_limited(&b"abcdef"[..], 5), None);
+    }
+
+    #[test]
+    fn gzip_layers_are_removed() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, b"some text").unwrap();
+        let data = encoder.finish().unwrap();
+        assert_eq!(decompress(data, "Notes.TXT.GZ".to_string()), Some((b"some text".to_vec(), "Notes.TXT".to_string())));
+    }
+}
@@ -1,19 +1,263 @@
 use super::paths::PathConfig;
+use super::extractors::{rtf_to_text, fb2_to_text, pdf_to_text, subtitles_to_text};
+use super::contents::{detect_headings, relocate_entries};
+use super::archives::{decompress, is_readable, ARCHIVE_EXTENSIONS};
+use super::preprocess::strip_boilerplate;
+use super::filters::{FilterRule, filter_presets, upgrade_filters};
+use super::keys::{KeyBinding, default_key_bindings};
+use crate::model::{LoadedText, TextMeta, Boundary};
 use serde::{Serialize, Deserialize};
+use derive_more::Display;
 use rfd::AsyncFileDialog;
 use pollster::FutureExt as _;
 use std::io::Write;
 
+// Files that can be turned into text by file_processor
+pub const TEXT_EXTENSIONS: [&str; 9] = ["txt", "csv", "md", "html", "pdf", "rtf", "fb2", "srt", "vtt"];
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
     pub font: String,
     pub text_size: f32,
     pub velocity: u32,
     pub theme: ColorTheme,
-    pub text_history: std::collections::HashMap<String, usize>
+    pub text_history: std::collections::HashMap<String, usize>,
+    #[serde(default)]
+    pub subtitle_timing: bool,
+    // Folder watched for new files to import
+    #[serde(default)]
+    pub inbox: Option<String>,
+    #[serde(default = "default_true")]
+    pub strip_boilerplate: bool,
+    #[serde(default)]
+    pub strip_back_matter: bool,
+    #[serde(default = "filter_presets")]
+    pub filters: Vec<FilterRule>,
+    #[serde(default)]
+    pub goal: ReadingGoal,
+    #[serde(default)]
+    pub quiz: QuizSettings,
+    #[serde(default)]
+    pub training: TrainingSettings,
+    #[serde(default)]
+    pub warmup: WarmupSettings,
+    #[serde(default)]
+    pub auto_pause: AutoPauseSettings,
+    #[serde(default = "default_key_bindings")]
+    pub keys: Vec<KeyBinding>,
+    #[serde(default)]
+    pub scroll: ScrollMode,
+    // Palettes saved by the user, next to the built-in presets
+    #[serde(default)]
+    pub themes: Vec<NamedTheme>,
+    #[serde(default)]
+    pub auto_theme: AutoTheme,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+fn default_true() -> bool {
+    true
+}
+
+// Cleanup applied to new texts before they are cached
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Preprocess {
+    pub strip_boilerplate: bool,
+    pub strip_back_matter: bool,
+}
+
+impl Config {
+    pub fn preprocess(&self) -> Preprocess {
+        Preprocess {
+            strip_boilerplate: self.strip_boilerplate,
+            strip_back_matter: self.strip_back_matter,
+        }
+    }
+}
+
+#[derive(Debug, Display, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub enum GoalKind {
+    #[default]
+    Minutes,
+    Words,
+}
+
+// Daily reading goal, an amount of 0 means no goal
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub struct ReadingGoal {
+    pub kind: GoalKind,
+    pub amount: u32,
+}
+
+impl ReadingGoal {
+    // Share of the goal reached, from 0 to 1, None without a goal
+    pub fn progress(&self, words: usize, reading_ms: u64) -> Option<f32> {
+        if self.amount == 0 {
+            return None;
+        }
+        let done = match self.kind {
+            GoalKind::Minutes => reading_ms as f32 / 60_000.0,
+            GoalKind::Words => words as f32,
+        };
+        Some((done / self.amount as f32).min(1.0))
+    }
+}
+
+// Comprehension questions asked at chapter ends and every interval words,
+// an interval of 0 means chapter ends only
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct QuizSettings {
+    pub enabled: bool,
+    pub interval: u32,
+}
+
+impl Default for QuizSettings {
+    fn default() -> Self {
+        QuizSettings { enabled: false, interval: 500 }
+    }
+}
+
+// Speed training: words per minute from the baseline, plus ramp per minute read,
+// with a drill_length seconds sprint at drill_boost percent every drill_interval seconds
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct TrainingSettings {
+    pub enabled: bool,
+    pub baseline: u32,
+    pub ramp: u32,
+    pub drill_interval: u32,
+    pub drill_length: u32,
+    pub drill_boost: u32,
+}
+
+impl Default for TrainingSettings {
+    fn default() -> Self {
+        TrainingSettings { enabled: false, baseline: 200, ramp: 10, drill_interval: 120, drill_length: 20, drill_boost: 150 }
+    }
+}
+
+// Pacing eases from start_speed percent of the target speed to the full
+// speed over the first words after a resume, a seek or a chapter start
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct WarmupSettings {
+    pub words: u32,
+    pub start_speed: u32,
+}
+
+impl Default for WarmupSettings {
+    fn default() -> Self {
+        WarmupSettings { words: 5, start_speed: 50 }
+    }
+}
+
+// What the vertical scroll wheel does over the word
+#[derive(Debug, Display, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub enum ScrollMode {
+    #[default]
+    #[display("Step words")]
+    Words,
+    #[display("Change speed")]
+    Speed,
+}
+
+#[derive(Debug, Display, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub enum PauseMode {
+    #[default]
+    Off,
+    Timed,
+    #[display("Wait for key")]
+    WaitForKey,
+}
+
+// What happens after the last word before a boundary, duration is in milliseconds
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub struct BoundaryPause {
+    pub mode: PauseMode,
+    pub duration: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AutoPauseSettings {
+    pub paragraph: BoundaryPause,
+    pub section: BoundaryPause,
+    pub chapter: BoundaryPause,
+}
+
+impl AutoPauseSettings {
+    pub fn get(&self, boundary: Boundary) -> BoundaryPause {
+        match boundary {
+            Boundary::Paragraph => self.paragraph,
+            Boundary::Section => self.section,
+            Boundary::Chapter => self.chapter,
+        }
+    }
+    pub fn get_mut(&mut self, boundary: Boundary) -> &mut BoundaryPause {
+        match boundary {
+            Boundary::Paragraph => &mut self.paragraph,
+            Boundary::Section => &mut self.section,
+            Boundary::Chapter => &mut self.chapter,
+        }
+    }
+}
+
+#[derive(Debug, Display, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub enum ThemeMode {
+    #[default]
+    #[display("Chosen theme")]
+    Manual,
+    #[display("Follow the system")]
+    System,
+    #[display("Schedule")]
+    Schedule,
+}
+
+// Time of day in minutes since midnight, shown as "07:30"
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct DayTime(pub u32);
+
+impl std::fmt::Display for DayTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.0 / 60 % 24, self.0 % 60)
+    }
+}
+
+// Switches between a light and a dark theme, by name, following the
+// system preference or the light_from/dark_from schedule
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AutoTheme {
+    pub mode: ThemeMode,
+    pub light: String,
+    pub dark: String,
+    pub light_from: DayTime,
+    pub dark_from: DayTime,
+}
+
+impl Default for AutoTheme {
+    fn default() -> Self {
+        AutoTheme {
+            mode: ThemeMode::Manual,
+            light: String::from("Light"),
+            dark: String::from("Dark"),
+            light_from: DayTime(7 * 60),
+            dark_from: DayTime(19 * 60),
+        }
+    }
+}
+
+impl AutoTheme {
+    // Whether the dark theme is due at this time of day, the light period may span midnight
+    pub fn scheduled_dark(&self, minutes: u32) -> bool {
+        let (light, dark) = (self.light_from.0, self.dark_from.0);
+        if light <= dark {
+            !(light..dark).contains(&minutes)
+        } else {
+            (dark..light).contains(&minutes)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ColorTheme {
     pub background: String,
     pub text: String,
@@ -23,6 +267,38 @@ pub struct ColorTheme {
     pub danger: String
 }
 
+impl ColorTheme {
+    fn is_valid(&self) -> bool {
+        [&self.background, &self.text, &self.primary, &self.success, &self.warning, &self.danger].iter()
+        .all(|color| color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit()))
+    }
+}
+
+// Theme files hold the name followed by the six colors
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NamedTheme {
+    pub name: String,
+    #[serde(flatten)]
+    pub colors: ColorTheme,
+}
+
+pub fn theme_file(theme: &NamedTheme) -> String {
+    toml::to_string(theme)
+    .expect("Invalid TOML format")
+}
+
+// Reads a theme file, a file with only the colors is named after the file
+pub fn parse_theme_file(data: &[u8], filename: &str) -> Option<NamedTheme> {
+    let content = std::str::from_utf8(data).ok()?;
+    let theme = toml::from_str::<NamedTheme>(content).ok()
+    .or_else(|| {
+        let colors = toml::from_str::<ColorTheme>(content).ok()?;
+        let name = filename.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(filename);
+        Some(NamedTheme { name: name.to_string(), colors })
+    })?;
+    (theme.colors.is_valid() && !theme.name.trim().is_empty()).then_some(theme)
+}
+
 pub fn get_config_path() -> PathConfig  {
     let mut result: PathConfig = Default::default();
     match std::env::consts::OS {
@@ -57,8 +333,10 @@ pub fn get_config_file(path: PathConfig) -> Config {
         match std::fs::read_to_string(format!("{}config.toml", path.get_config())) {
             Ok(content) => {
                 // Deserialize into struct
-                toml::from_str(&content)
-                .expect("Invalid TOML format")
+                let mut config: Config = toml::from_str(&content)
+                .expect("Invalid TOML format");
+                upgrade_filters(&mut config.filters);
+                config
             },
             Err(_) => {
                 // Create object
@@ -75,6 +353,20 @@ pub fn get_config_file(path: PathConfig) -> Config {
                         danger: String::from("#DF3535"),
                     },
                     text_history: std::collections::HashMap::new(),
+                    subtitle_timing: false,
+                    inbox: None,
+                    strip_boilerplate: true,
+                    strip_back_matter: false,
+                    filters: filter_presets(),
+                    goal: ReadingGoal::default(),
+                    quiz: QuizSettings::default(),
+                    training: TrainingSettings::default(),
+                    warmup: WarmupSettings::default(),
+                    auto_pause: AutoPauseSettings::default(),
+                    keys: default_key_bindings(),
+                    scroll: ScrollMode::Words,
+                    themes: Vec::new(),
+                    auto_theme: AutoTheme::default(),
                 };
                 // Serialize into toml string 
                 let toml_string = toml::to_string(&config)
@@ -114,7 +406,7 @@ pub fn open_file_dialog() -> Option<(Vec<u8>, String)> {
     let future = async {
         let file = AsyncFileDialog::new()
         .set_title("Choose any text file to being read")
-        .add_filter("text", &["txt", "csv", "md", "html", "pdf"])
+        .add_filter("text", &[&TEXT_EXTENSIONS[..], &ARCHIVE_EXTENSIONS[..]].concat())
         .set_directory("/")
         .pick_file()
         .await;
@@ -133,105 +425,187 @@ pub fn open_file_dialog() -> Option<(Vec<u8>, String)> {
     future.block_on()
 }
 
-pub async fn file_processor(text_history: std::collections::HashMap<String, usize>, path: PathConfig, data: Vec<u8>, filename: String) -> Option<(String, String, usize)> {
-    let filename_collection = filename.split(".").collect::<Vec<&str>>();
-    let filename_ext = filename_collection.last().copied();
+pub fn open_theme_dialog() -> Option<(Vec<u8>, String)> {
+    let future = async {
+        let file = AsyncFileDialog::new()
+        .set_title("Choose a theme file to import")
+        .add_filter("theme", &["toml"])
+        .pick_file()
+        .await?;
+        Some((file.read().await, file.file_name()))
+    };
+    future.block_on()
+}
+
+// Writes the content to a file picked by the user
+// Fails only when writing the chosen file does, a cancelled dialog is fine
+pub fn save_file_dialog(default_name: &str, extension: &str, content: String) -> std::io::Result<()> {
+    let future = async {
+        let file = AsyncFileDialog::new()
+        .set_title("Choose where to export")
+        .set_file_name(format!("{}.{}", default_name, extension))
+        .add_filter(extension, &[extension])
+        .save_file()
+        .await;
+
+        match file {
+            Some(file) => file.write(content.as_bytes()).await,
+            None => Ok(()),
+        }
+    };
+    future.block_on()
+}
+
+pub fn open_folder_dialog() -> Option<String> {
+    let future = async {
+        AsyncFileDialog::new()
+        .set_title("Choose the folder to import new files from")
+        .pick_folder()
+        .await
+        .map(|folder| folder.path().to_string_lossy().to_string())
+    };
+    future.block_on()
+}
+
+pub async fn file_processor(text_history: std::collections::HashMap<String, usize>, path: PathConfig, data: Vec<u8>, filename: String, preprocess: Preprocess) -> Option<LoadedText> {
+    // Compressed inputs are read as the file they contain, "notes.gz" holds nothing readable
+    let (data, filename) = decompress(data, filename)?;
+    if !is_readable(&filename) {
+        return None;
+    }
+    let filename_ext = filename.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
     let clean_filename = std::path::Path::new(&filename).file_stem()
-    .and_then(|s| s.to_str())
-    .unwrap().to_string();
+    .and_then(|s| s.to_str())?
+    .to_string();
 
     // If txt already exists then return txt content, else save it
     match std::fs::read_to_string(format!("{}texts/{}.txt", path.get_data(), clean_filename)) {
         // Send saved index
         Ok(content) => {
-            Some((content, clean_filename.clone(), text_history.get(&clean_filename).copied().unwrap_or(0)))
+            let meta = get_text_meta(&path, &clean_filename);
+            let index = text_history.get(&clean_filename).copied().unwrap_or(0);
+            Some(LoadedText { content, name: clean_filename, meta, index, adhoc: false })
         },
         Err(_) => {
-            match filename_ext {
+            let (mut s, mut meta) = match filename_ext.as_deref() {
                 Some("txt") | Some("csv") | Some("md") => {
-                    let s = String::from_utf8_lossy(&data).to_string();
-                    let _ = std::fs::write(format!("{}texts/{}.txt", path.get_data(), clean_filename), &s);
-                    Some((s, clean_filename, 0))
+                    (String::from_utf8_lossy(&data).to_string(), TextMeta::default())
                 },
                 Some("pdf") => {
-                    let cursor = std::io::Cursor::new(data);
-                    let reader = oxidize_pdf::parser::PdfReader::new(cursor).ok()?;
-                    let doc = oxidize_pdf::parser::PdfDocument::new(reader);
-                    let pages = doc.extract_text().ok()?;
-
-                    let text_page = pages.into_iter().map(|x| x.text);
-                    let s = text_page.collect::<Vec<String>>().join("");
-                    
-                    if s.is_empty() {
-                        panic!("Error: result string is empty");
-                    } else {
-                        let _ = std::fs::write(format!("{}texts/{}.txt", path.get_data(), clean_filename), &s);
-                        Some((s, clean_filename, 0))
-                    }
+                    // Scanned PDFs without a text layer have nothing to read
+                    let (s, toc) = pdf_to_text(data).filter(|(s, _)| !s.trim().is_empty())?;
+                    (s, TextMeta { toc, ..Default::default() })
                 },
                 Some("html") => {
-                    let s = html2text::from_read(&data[..], 80).ok();
-                    let _ = std::fs::write(format!("{}texts/{}.txt", path.get_data(), clean_filename), s.clone().unwrap());
-                    Some((s?, clean_filename, 0))
+                    (html2text::from_read(&data[..], 80).ok()?, TextMeta::default())
+                },
+                Some("rtf") => {
+                    (rtf_to_text(&data), TextMeta::default())
                 },
-                _ => {panic!("Unsupported file type")}
+                Some("fb2") => {
+                    let (s, title, toc) = fb2_to_text(&data)?;
+                    (s, TextMeta { title, toc, ..Default::default() })
+                },
+                Some("srt") | Some("vtt") => {
+                    let (s, cues) = subtitles_to_text(&data);
+                    (s, TextMeta { cues, ..Default::default() })
+                },
+                _ => return None
+            };
+            // Subtitles are kept whole so cues still match their words
+            if preprocess.strip_boilerplate && meta.cues.is_empty() {
+                let (content, stripped) = strip_boilerplate(&s, preprocess.strip_back_matter);
+                meta.toc = relocate_entries(&s, &content, meta.toc);
+                s = content;
+                meta.stripped = stripped;
             }
+            // Formats without a structure of their own get their headings guessed
+            if meta.toc.is_empty() && meta.cues.is_empty() {
+                meta.toc = detect_headings(&s);
+            }
+            save_text(&path, &clean_filename, &s, &meta);
+            Some(LoadedText { content: s, name: clean_filename, meta, index: 0, adhoc: false })
         }
     }
 }
 
-pub fn open_file_dialog_font(path: PathConfig) -> Option<(String, Vec<u8>)> {
+// Adds a text and its metadata to the cached library
+// Name no cached text uses yet, "clipboard-1700000000-2" when "clipboard-1700000000" is taken
+pub fn unused_text_name(path: &PathConfig, name: &str) -> String {
+    std::iter::once(name.to_string())
+    .chain((2..).map(|n| format!("{}-{}", name, n)))
+    .find(|candidate| !std::path::Path::new(&format!("{}texts/{}.txt", path.get_data(), candidate)).exists())
+    .unwrap_or_else(|| name.to_string())
+}
+
+pub fn save_text(path: &PathConfig, name: &str, content: &str, meta: &TextMeta) {
+    let _ = std::fs::write(format!("{}texts/{}.txt", path.get_data(), name), content);
+    save_text_meta(path, name, meta);
+}
+
+pub fn get_text_meta(path: &PathConfig, name: &str) -> TextMeta {
+    std::fs::read_to_string(format!("{}texts/{}.toml", path.get_data(), name))
+    .ok()
+    .and_then(|content| toml::from_str(&content).ok())
+    .unwrap_or_default()
+}
+
+pub fn save_text_meta(path: &PathConfig, name: &str, meta: &TextMeta) {
+    let toml_string = toml::to_string(meta)
+    .expect("Invalid TOML format");
+    let _ = std::fs::write(format!("{}texts/{}.toml", path.get_data(), name), toml_string);
+}
+
+// Formats ttf-parser reads, WOFF and WOFF2 are compressed and it can't
+pub const FONT_EXTENSIONS: [&str; 2] = ["ttf", "otf"];
+
+pub fn open_file_dialog_font() -> Option<Vec<u8>> {
     let future = async {
         let file = AsyncFileDialog::new()
         .set_title("Choose any font file to be used")
-        .add_filter("font", &["ttf", "otf", "woff", "woff2"])
+        .add_filter("font", &FONT_EXTENSIONS)
         .set_directory("/")
         .pick_file()
         .await;
 
-        if file.as_ref().is_some() {
-            let data = file.unwrap().read().await;
-
-            let parsed_data = ttf_parser::Face::parse(&data, 0).unwrap();
-
-            let name = parsed_data.names()
-            .into_iter()
-            .find_map(|label| {
-                if label.name_id == ttf_parser::name_id::FAMILY {
-                    label.to_string()
-                } else {
-                    None
-                }
-            });
-
-            if let Some(family_name) = name {
-                // Save file if doesn't already exists
-                let new_file = std::fs::OpenOptions::new()
-                .write(true)
-                .create_new(true)
-                .open(format!("{}fonts/{}", path.get_data(), family_name));
-
-                match new_file {
-                    Ok(mut f) => {
-                        f.write_all(&data).expect("Error writing file");
-                        Some((family_name, data))
-                    },
-                    Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
-                        Some((family_name, data))
-                    },
-                    Err(e) => {
-                        panic!("Error creating file: {}", e)
-                    }
-                }
-            } else {
-                panic!("Error getting font family name")
-            }
+        Some(file?.read().await)
+
+    };
+    future.block_on()
+}
+
+// Saves the font in the data fonts folder, named after its family
+// None when the file isn't a font or can't be saved
+pub fn load_font_file(path: PathConfig, data: Vec<u8>) -> Option<(String, Vec<u8>)> {
+    let parsed_data = ttf_parser::Face::parse(&data, 0).ok()?;
+
+    let name = parsed_data.names()
+    .into_iter()
+    .find_map(|label| {
+        if label.name_id == ttf_parser::name_id::FAMILY {
+            label.to_string()
         } else {
             None
         }
+    });
 
-    };
-    future.block_on()
+    let family_name = name?;
+    // Save file if doesn't already exists
+    let new_file = std::fs::OpenOptions::new()
+    .write(true)
+    .create_new(true)
+    .open(format!("{}fonts/{}", path.get_data(), family_name));
+
+    match new_file {
+        Ok(mut f) => {
+            f.write_all(&data).ok()?;
+            Some((family_name, data))
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            Some((family_name, data))
+        },
+        Err(_) => None
+    }
 }
 
 pub fn get_font(path: PathConfig, config: &Config) -> Option<(String, Vec<u8>)> {
@@ -243,3 +617,51 @@ pub fn get_font(path: PathConfig, config: &Config) -> Option<(String, Vec<u8>)>
 
     Some((font_name.clone(), std::fs::read(format!("{}/fonts/{}", path.get_data(), font_name)).unwrap()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pasted_texts_get_a_free_name() {
+        let dir = std::env::temp_dir().join(format!("rsvp-names-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("texts")).unwrap();
+        let mut path = PathConfig::default();
+        path.set_data(format!("{}/", dir.display()));
+        assert_eq!(unused_text_name(&path, "clipboard-1"), "clipboard-1");
+        save_text(&path, "clipboard-1", "first", &TextMeta::default());
+        assert_eq!(unused_text_name(&path, "clipboard-1"), "clipboard-1-2");
+        save_text(&path, "clipboard-1-2", "second", &TextMeta::default());
+        assert_eq!(unused_text_name(&path, "clipboard-1"), "clipboard-1-3");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn goal_progress_by_minutes_or_words() {
+        let minutes = ReadingGoal { kind: GoalKind::Minutes, amount: 20 };
+        assert_eq!(minutes.progress(5000, 600_000), Some(0.5));
+        let words = ReadingGoal { kind: GoalKind::Words, amount: 1000 };
+        assert_eq!(words.progress(250, 3_600_000), Some(0.25));
+        // Going past the goal stays at full
+        assert_eq!(words.progress(5000, 0), Some(1.0));
+    }
+
+    #[test]
+    fn zero_amount_means_no_goal() {
+        assert_eq!(ReadingGoal { kind: GoalKind::Minutes, amount: 0 }.progress(100, 60_000), None);
+        assert_eq!(ReadingGoal::default().progress(0, 0), None);
+    }
+
+    #[test]
+    fn schedule_follows_the_light_period() {
+        let auto = AutoTheme::default();
+        assert!(auto.scheduled_dark(6 * 60 + 59));
+        assert!(!auto.scheduled_dark(7 * 60));
+        assert!(auto.scheduled_dark(19 * 60));
+        // A light period spanning midnight
+        let night_shift = AutoTheme { light_from: DayTime(22 * 60), dark_from: DayTime(6 * 60), ..AutoTheme::default() };
+        assert!(!night_shift.scheduled_dark(23 * 60));
+        assert!(!night_shift.scheduled_dark(60));
+        assert!(night_shift.scheduled_dark(12 * 60));
+    }
+}
@@ -0,0 +1,121 @@
+use crate::model::{Boundary, TocEntry};
+
+const CHAPTER_WORDS: [&str; 6] = ["part", "book", "chapter", "prologue", "epilogue", "interlude"];
+
+// Markdown style headings, also what html2text renders HTML headings as,
+// otherwise lines that look like "CHAPTER XII" or "Part Two"
+pub fn detect_headings(content: &str) -> Vec<TocEntry> {
+    let markdown: Vec<TocEntry> = content.lines().enumerate()
+    .filter_map(|(line, text)| {
+        let level = text.chars().take_while(|c| *c == '#').count();
+        let title = text[level..].strip_prefix(' ')?.trim().trim_end_matches('#').trim();
+        ((1..=6).contains(&level) && !title.is_empty())
+        .then(|| TocEntry { title: title.to_string(), level, line })
+    })
+    .collect();
+    if !markdown.is_empty() {
+        return normalize_levels(markdown);
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let entries = lines.iter().enumerate()
+    .filter_map(|(line, text)| {
+        let text = text.trim();
+        let first = text.split_whitespace().next()?.trim_end_matches([':', '.']).to_lowercase();
+        let word_count = text.split_whitespace().count();
+        // Headings stand on their own, after a blank line
+        let after_blank = line == 0 || lines[line - 1].trim().is_empty();
+        if !CHAPTER_WORDS.contains(&first.as_str()) || word_count > 8 || !after_blank {
+            return None;
+        }
+        // A number has to follow, except for prologues and the like
+        let numbered = text.split_whitespace().nth(1)
+        .map(|w| w.trim_end_matches([':', '.']))
+        .is_some_and(is_chapter_number);
+        let level = if first == "part" || first == "book" { 1 } else { 2 };
+        (numbered || level == 2 && word_count == 1 && first != "chapter")
+        .then(|| TocEntry { title: text.to_string(), level, line })
+    })
+    .collect();
+    normalize_levels(entries)
+}
+
+fn is_chapter_number(word: &str) -> bool {
+    const NUMBER_WORDS: [&str; 20] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+        "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen",
+        "eighteen", "nineteen", "twenty",
+    ];
+    let word = word.to_lowercase();
+    word.chars().all(|c| c.is_ascii_digit())
+    || word.chars().all(|c| "ivxlc".contains(c))
+    || NUMBER_WORDS.iter().any(|n| word.starts_with(n))
+}
+
+// The outermost level found becomes level 1
+fn normalize_levels(mut entries: Vec<TocEntry>) -> Vec<TocEntry> {
+    let top = entries.iter().map(|e| e.level).min().unwrap_or(1);
+    for entry in &mut entries {
+        entry.level = entry.level + 1 - top;
+    }
+    entries
+}
+
+// Moves entries found in the extracted text onto the same lines of the
+// preprocessed one, entries whose line was stripped are dropped
+pub fn relocate_entries(before: &str, after: &str, entries: Vec<TocEntry>) -> Vec<TocEntry> {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+    let mut cursor = 0;
+    entries.into_iter()
+    .filter_map(|mut entry| {
+        let text = before.get(entry.line)?.trim();
+        let line = after[cursor..].iter().position(|l| !text.is_empty() && l.trim() == text)? + cursor;
+        // Several entries can point to the same page start
+        cursor = line;
+        entry.line = line;
+        Some(entry)
+    })
+    .collect()
+}
+
+// Words followed by a blank line end a paragraph, those followed by a
+// scene break like "* * *" end a section
+pub fn text_boundaries(content: &str) -> Vec<(usize, Boundary)> {
+    let mut boundaries: Vec<(usize, Boundary)> = Vec::new();
+    let mut words = 0;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if words > 0 && (trimmed.is_empty() || is_scene_break(trimmed)) {
+            let boundary = if trimmed.is_empty() { Boundary::Paragraph } else { Boundary::Section };
+            match boundaries.last_mut() {
+                Some(last) if last.0 == words - 1 => last.1 = last.1.max(boundary),
+                _ => boundaries.push((words - 1, boundary)),
+            }
+        }
+        words += line.split_whitespace().count();
+    }
+    boundaries
+}
+
+fn is_scene_break(line: &str) -> bool {
+    line == "§" || line.chars().filter(|c| !c.is_whitespace()).count() >= 3
+    && line.chars().all(|c| c.is_whitespace() || matches!(c, '*' | '#' | '~' | '-' | '='))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_lines_and_scene_breaks_end_paragraphs_and_sections() {
+        let content = "One two.\n\nThree.\n* * *\nFour five.";
+        assert_eq!(text_boundaries(content), vec![(1, Boundary::Paragraph), (2, Boundary::Section)]);
+    }
+
+    #[test]
+    fn chapter_lines_become_headings() {
+        let headings = detect_headings("CHAPTER I\n\nIt was a dark night.\n\nChapter 2\n\nMorning came.");
+        assert_eq!(headings.iter().map(|h| h.line).collect::<Vec<_>>(), vec![0, 4]);
+    }
+}
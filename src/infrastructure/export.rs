@@ -0,0 +1,26 @@
+use serde::Serialize;
+use crate::model::Highlight;
+
+#[derive(Serialize)]
+struct HighlightsExport<'a> {
+    title: &'a str,
+    highlights: &'a [Highlight],
+}
+
+pub fn highlights_to_markdown(title: &str, highlights: &[Highlight]) -> String {
+    let mut result = format!("# {}\n\n", title);
+    for highlight in highlights {
+        result.push_str(&format!("> {}\n\n", highlight.text));
+        result.push_str(&format!("*Words {}-{}*\n\n", highlight.start + 1, highlight.end));
+        if !highlight.note.trim().is_empty() {
+            result.push_str(&format!("{}\n\n", highlight.note.trim()));
+        }
+        result.push_str("---\n\n");
+    }
+    result
+}
+
+pub fn highlights_to_json(title: &str, highlights: &[Highlight]) -> String {
+    serde_json::to_string_pretty(&HighlightsExport { title, highlights })
+    .expect("Invalid JSON format")
+}
@@ -0,0 +1,465 @@
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use oxidize_pdf::parser::{PdfDictionary, PdfDocument, PdfObject, PdfReader};
+use crate::model::{Cue, TocEntry};
+use encoding_rs::Encoding;
+
+// Destinations whose content is never shown as text
+const RTF_SKIPPED_DESTINATIONS: [&str; 12] = [
+    "fonttbl", "colortbl", "stylesheet", "info", "pict", "header", "footer",
+    "headerl", "headerr", "footerl", "footerr", "object",
+];
+
+// Encoding of an RTF \ansicpg code page, Windows-1252 when it is unknown
+fn code_page_encoding(code_page: i32) -> &'static Encoding {
+    match code_page {
+        874 => encoding_rs::WINDOWS_874,
+        932 => encoding_rs::SHIFT_JIS,
+        936 => encoding_rs::GBK,
+        949 => encoding_rs::EUC_KR,
+        950 => encoding_rs::BIG5,
+        1250..=1258 => Encoding::for_label(format!("windows-{}", code_page).as_bytes()).unwrap_or(encoding_rs::WINDOWS_1252),
+        10000 => encoding_rs::MACINTOSH,
+        20866 => encoding_rs::KOI8_R,
+        65001 => encoding_rs::UTF_8,
+        _ => encoding_rs::WINDOWS_1252,
+    }
+}
+
+// Decodes the bytes collected since the last flush, multi-byte code pages
+// need every byte of a character before decoding it
+fn flush_rtf_bytes(result: &mut String, bytes: &mut Vec<u8>, encoding: &'static Encoding) {
+    if !bytes.is_empty() {
+        result.push_str(&encoding.decode_without_bom_handling(bytes).0);
+        bytes.clear();
+    }
+}
+
+pub fn rtf_to_text(data: &[u8]) -> String {
+    let mut result = String::new();
+    // Text bytes waiting to be decoded with the document code page
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut encoding = encoding_rs::WINDOWS_1252;
+    // One flag per open group, true when the group content is hidden
+    let mut skip_stack: Vec<bool> = vec![false];
+    // Characters to drop after a \uN, its ANSI fallback
+    let mut unicode_skip: usize = 1;
+    let mut pending_skip: usize = 0;
+    let mut i = 0;
+
+    while i < data.len() {
+        let skip = *skip_stack.last().unwrap_or(&true);
+        let text_byte = match data[i] {
+            b'{' | b'}' => false,
+            b'\\' => data.get(i + 1) == Some(&b'\''),
+            _ => true,
+        };
+        if !text_byte {
+            flush_rtf_bytes(&mut result, &mut bytes, encoding);
+        }
+        match data[i] {
+            b'{' => {
+                skip_stack.push(skip);
+                i += 1;
+            },
+            b'}' => {
+                skip_stack.pop();
+                if skip_stack.is_empty() {
+                    break;
+                }
+                i += 1;
+            },
+            b'\\' => {
+                i += 1;
+                let Some(&next) = data.get(i) else { break };
+
+                if next.is_ascii_alphabetic() {
+                    // Control word: letters, optional signed number, optional space delimiter
+                    let start = i;
+                    while i < data.len() && data[i].is_ascii_alphabetic() {
+                        i += 1;
+                    }
+                    let word = String::from_utf8_lossy(&data[start..i]).to_string();
+                    let param_start = i;
+                    if i < data.len() && data[i] == b'-' {
+                        i += 1;
+                    }
+                    while i < data.len() && data[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                    let param = std::str::from_utf8(&data[param_start..i]).ok()
+                    .and_then(|p| p.parse::<i32>().ok());
+                    if i < data.len() && data[i] == b' ' {
+                        i += 1;
+                    }
+
+                    match word.as_str() {
+                        w if RTF_SKIPPED_DESTINATIONS.contains(&w) => {
+                            if let Some(last) = skip_stack.last_mut() {
+                                *last = true;
+                            }
+                        },
+                        "ansicpg" => encoding = code_page_encoding(param.unwrap_or(1252)),
+                        // Binary data of that many bytes follows, never text
+                        "bin" => i += param.unwrap_or(0).max(0) as usize,
+                        "uc" => unicode_skip = param.unwrap_or(1).max(0) as usize,
+                        "u" if !skip => {
+                            // Negative values are used for code points above 32767
+                            let code = param.unwrap_or(0);
+                            let code = if code < 0 { code + 65536 } else { code };
+                            if let Some(c) = char::from_u32(code as u32) {
+                                result.push(c);
+                            }
+                            pending_skip = unicode_skip;
+                        },
+                        "par" | "line" | "sect" | "page" if !skip => result.push('\n'),
+                        "tab" if !skip => result.push(' '),
+                        "emdash" if !skip => result.push('—'),
+                        "endash" if !skip => result.push('–'),
+                        "lquote" if !skip => result.push('‘'),
+                        "rquote" if !skip => result.push('’'),
+                        "ldblquote" if !skip => result.push('“'),
+                        "rdblquote" if !skip => result.push('”'),
+                        "bullet" if !skip => result.push('•'),
+                        _ => {}
+                    }
+                } else {
+                    // Control symbol
+                    i += 1;
+                    match next {
+                        b'*' => {
+                            if let Some(last) = skip_stack.last_mut() {
+                                *last = true;
+                            }
+                        },
+                        b'\'' => {
+                            let hex = data.get(i..i + 2)
+                            .and_then(|h| std::str::from_utf8(h).ok())
+                            .and_then(|h| u8::from_str_radix(h, 16).ok());
+                            i += 2;
+                            if let Some(byte) = hex && !skip {
+                                if pending_skip > 0 {
+                                    pending_skip -= 1;
+                                } else {
+                                    bytes.push(byte);
+                                }
+                            }
+                        },
+                        b'\\' | b'{' | b'}' if !skip => result.push(next as char),
+                        b'~' if !skip => result.push(' '),
+                        b'_' if !skip => result.push('-'),
+                        b'\n' | b'\r' if !skip => result.push('\n'),
+                        _ => {}
+                    }
+                }
+            },
+            b'\n' | b'\r' => i += 1,
+            byte => {
+                if !skip {
+                    if pending_skip > 0 {
+                        pending_skip -= 1;
+                    } else {
+                        bytes.push(byte);
+                    }
+                }
+                i += 1;
+            }
+        }
+    }
+    flush_rtf_bytes(&mut result, &mut bytes, encoding);
+    result
+}
+
+// Encoding from a byte order mark or the XML declaration, UTF-8 otherwise
+fn xml_encoding(data: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(data) {
+        return encoding;
+    }
+    let head = String::from_utf8_lossy(&data[..data.len().min(200)]);
+    head.strip_prefix("<?xml")
+    .and_then(|declaration| declaration.split("?>").next())
+    .and_then(|declaration| declaration.split_once("encoding"))
+    .and_then(|(_, rest)| rest.split(['"', '\'']).nth(1))
+    .and_then(|label| Encoding::for_label(label.trim().as_bytes()))
+    .unwrap_or(encoding_rs::UTF_8)
+}
+
+// Returns the book text, a "Author - Title" display title and the section titles
+pub fn fb2_to_text(data: &[u8]) -> Option<(String, String, Vec<TocEntry>)> {
+    // Russian books are often windows-1251, the parser is given UTF-8
+    let (content, _, _) = xml_encoding(data).decode(data);
+    let mut reader = Reader::from_reader(content.as_bytes());
+
+    let mut buf = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut result = String::new();
+    let mut author: Vec<String> = Vec::new();
+    let mut book_title = String::new();
+    let mut author_done = false;
+    let mut toc: Vec<TocEntry> = Vec::new();
+    // Where the text of the section title being read starts
+    let mut title_start: Option<usize> = None;
+
+    loop {
+        match reader.read_event_into(&mut buf).ok()? {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if name == "section" || name == "title" {
+                    result.push('\n');
+                }
+                if name == "title" && in_body(&path) && path.iter().any(|p| p == "section") {
+                    title_start = Some(result.len());
+                }
+                path.push(name);
+            },
+            Event::End(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match name.as_str() {
+                    "p" | "v" | "subtitle" | "text-author" if in_body(&path) => result.push('\n'),
+                    "title" if in_body(&path) => {
+                        if let Some(start) = title_start.take() {
+                            let title_text = &result[start..];
+                            let title = title_text.split_whitespace().collect::<Vec<&str>>().join(" ");
+                            let first_char = title_text.len() - title_text.trim_start().len();
+                            if !title.is_empty() {
+                                toc.push(TocEntry {
+                                    title,
+                                    level: path.iter().filter(|p| *p == "section").count(),
+                                    line: result[..start + first_char].matches('\n').count(),
+                                });
+                            }
+                        }
+                        result.push('\n');
+                    },
+                    "author" if path.iter().any(|p| p == "title-info") => author_done = true,
+                    _ => {}
+                }
+                path.pop();
+            },
+            Event::Empty(e) if e.local_name().as_ref() == b"empty-line" => {
+                result.push('\n');
+            },
+            Event::Text(e) => {
+                let text = e.decode().ok()?;
+                push_fb2_text(&path, &text, &mut result, &mut author, author_done, &mut book_title);
+            },
+            Event::CData(e) => {
+                let text = e.decode().ok()?;
+                push_fb2_text(&path, &text, &mut result, &mut author, author_done, &mut book_title);
+            },
+            Event::GeneralRef(e) => {
+                let entity = e.decode().ok()?;
+                let text = match e.resolve_char_ref().ok()? {
+                    Some(c) => c.to_string(),
+                    None => quick_xml::escape::resolve_predefined_entity(&entity)
+                    .unwrap_or_default()
+                    .to_string(),
+                };
+                push_fb2_text(&path, &text, &mut result, &mut author, author_done, &mut book_title);
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    let author = author.join(" ");
+    let book_title = book_title.trim().to_string();
+    let title = match (author.is_empty(), book_title.is_empty()) {
+        (false, false) => format!("{} - {}", author, book_title),
+        (true, false) => book_title,
+        (false, true) => author,
+        (true, true) => String::new(),
+    };
+    Some((result, title, toc))
+}
+
+fn in_body(path: &[String]) -> bool {
+    path.iter().any(|p| p == "body") && !path.iter().any(|p| p == "binary")
+}
+
+fn push_fb2_text(path: &[String], text: &str, result: &mut String, author: &mut Vec<String>, author_done: bool, book_title: &mut String) {
+    let current = path.last().map(|s| s.as_str()).unwrap_or("");
+    let in_title_info = path.iter().any(|p| p == "title-info");
+
+    if in_title_info && !author_done && path.iter().any(|p| p == "author")
+    && matches!(current, "first-name" | "middle-name" | "last-name") {
+        author.push(text.trim().to_string());
+    } else if in_title_info && current == "book-title" {
+        book_title.push_str(text);
+    } else if in_body(path) && !(text.trim().is_empty() && text.contains('\n')) {
+        // Indentation between elements is skipped, inline spacing is kept
+        result.push_str(text);
+    }
+}
+
+// Returns the text of every page and the document outline, each bookmark
+// pointing to the line where its page starts
+pub fn pdf_to_text(data: Vec<u8>) -> Option<(String, Vec<TocEntry>)> {
+    let cursor = std::io::Cursor::new(data);
+    let mut reader = PdfReader::new(cursor).ok()?;
+    let outlines = reader.catalog().ok().and_then(|catalog| catalog.get("Outlines").cloned());
+    let doc = PdfDocument::new(reader);
+    let pages = doc.extract_text().ok()?;
+
+    let mut result = String::new();
+    let mut page_lines: Vec<usize> = Vec::new();
+    // Pages start on a new line so their first and last words don't stick together
+    for page in pages {
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push('\n');
+        }
+        page_lines.push(result.matches('\n').count());
+        result.push_str(&page.text);
+    }
+
+    let page_refs: Vec<(u32, u16)> = (0..doc.page_count().unwrap_or(0))
+    .filter_map(|i| doc.get_page(i).ok().map(|page| page.obj_ref))
+    .collect();
+    let mut toc: Vec<TocEntry> = Vec::new();
+    if let Some(first) = outlines
+        .and_then(|outlines| doc.resolve(&outlines).ok())
+        .and_then(|outlines| outlines.as_dict().and_then(|d| d.get("First")).cloned()) {
+            let mut visited: Vec<(u32, u16)> = Vec::new();
+            push_outline_items(&doc, first, 1, &page_refs, &page_lines, &mut visited, &mut toc);
+    }
+    Some((result, toc))
+}
+
+// Walks an outline level through /Next, and its children through /First
+fn push_outline_items<R: std::io::Read + std::io::Seek>(doc: &PdfDocument<R>, mut item: PdfObject, level: usize,
+    page_refs: &[(u32, u16)], page_lines: &[usize], visited: &mut Vec<(u32, u16)>, toc: &mut Vec<TocEntry>) {
+    // Broken files can link items in a loop
+    while let Some(reference) = item.as_reference() && !visited.contains(&reference) && level <= 16 {
+        visited.push(reference);
+        let Ok(resolved) = doc.resolve(&item) else { return };
+        let Some(dict) = resolved.as_dict() else { return };
+
+        let title = dict.get("Title").and_then(|t| t.as_string()).map(|t| decode_pdf_string(t.as_bytes()));
+        // Pages without extracted text have no line to point at
+        let line = outline_page(doc, dict, page_refs).and_then(|page| page_lines.get(page).copied());
+        if let (Some(title), Some(line)) = (title, line) && !title.trim().is_empty() {
+            toc.push(TocEntry { title: title.trim().to_string(), level, line });
+        }
+        if let Some(child) = dict.get("First").cloned() {
+            push_outline_items(doc, child, level + 1, page_refs, page_lines, visited, toc);
+        }
+        match dict.get("Next").cloned() {
+            Some(next) => item = next,
+            None => return,
+        }
+    }
+}
+
+// Page index of a /Dest array or of a /GoTo action, named destinations are not followed
+fn outline_page<R: std::io::Read + std::io::Seek>(doc: &PdfDocument<R>, dict: &PdfDictionary, page_refs: &[(u32, u16)]) -> Option<usize> {
+    let dest = match dict.get("Dest") {
+        Some(dest) => dest.clone(),
+        None => {
+            let action = doc.resolve(dict.get("A")?).ok()?;
+            action.as_dict()?.get("D")?.clone()
+        }
+    };
+    let dest = doc.resolve(&dest).ok()?;
+    match dest.as_array()?.get(0)? {
+        // Some writers use the page number instead of a page reference
+        PdfObject::Integer(page) => usize::try_from(*page).ok().filter(|page| *page < page_refs.len()),
+        page => page_refs.iter().position(|r| Some(*r) == page.as_reference()),
+    }
+}
+
+// UTF-16 when it starts with a byte order mark, PDFDocEncoding (close to Latin-1) otherwise
+fn decode_pdf_string(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFE, 0xFF, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&units)
+        },
+        _ => bytes.iter().map(|b| char::from(*b)).collect(),
+    }
+}
+
+// Works for both SRT and WebVTT, cue numbers, identifiers and timestamps are dropped
+pub fn subtitles_to_text(data: &[u8]) -> (String, Vec<Cue>) {
+    let content = String::from_utf8_lossy(data).replace("\r\n", "\n");
+    let mut result = String::new();
+    let mut cues: Vec<Cue> = Vec::new();
+
+    for block in content.split("\n\n") {
+        let lines: Vec<&str> = block.lines().collect();
+        // Header, NOTE, STYLE and REGION blocks have no timing line
+        let Some(timing_line) = lines.iter().position(|l| l.contains("-->")) else { continue };
+        let mut times = lines[timing_line].split("-->");
+        let start = times.next().and_then(parse_timestamp);
+        let end = times.next().and_then(|t| t.split_whitespace().next()).and_then(parse_timestamp);
+        let (Some(start), Some(end)) = (start, end) else { continue };
+
+        let text = lines[timing_line + 1..].iter()
+        .map(|l| strip_subtitle_tags(l))
+        .collect::<Vec<String>>()
+        .join(" ");
+        let words = text.split_whitespace().count();
+        if words == 0 {
+            continue;
+        }
+        result.push_str(text.trim());
+        result.push('\n');
+        cues.push(Cue { start, end, words });
+    }
+    (result, cues)
+}
+
+// "01:02:03,456" (SRT) or "02:03.456" (VTT) into milliseconds
+fn parse_timestamp(s: &str) -> Option<u64> {
+    let s = s.trim().replace(',', ".");
+    let (clock, millis) = s.split_once('.').unwrap_or((&s, "0"));
+    let millis: u64 = millis.parse().ok()?;
+    let seconds = clock.split(':')
+    .try_fold(0u64, |acc, part| part.parse::<u64>().ok().map(|p| acc * 60 + p))?;
+    Some(seconds * 1000 + millis)
+}
+
+// Removes <i>, <c.name>, <00:01.000> style tags and {\an8} style overrides
+fn strip_subtitle_tags(line: &str) -> String {
+    let mut result = String::new();
+    let mut closing: Option<char> = None;
+    for c in line.chars() {
+        match (closing, c) {
+            (None, '<') => closing = Some('>'),
+            (None, '{') => closing = Some('}'),
+            (Some(end), c) if c == end => closing = None,
+            (None, c) => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rtf_follows_the_code_page() {
+        let rtf = br"{\rtf1\ansi\ansicpg1251 {\fonttbl{\f0 Arial;}}\f0 \'cf\'f0\'e8\'e2\'e5\'f2}";
+        assert_eq!(rtf_to_text(rtf), "Привет");
+        assert_eq!(rtf_to_text(br"{\rtf1\ansi caf\'e9}"), "café");
+    }
+
+    #[test]
+    fn rtf_skips_binary_data() {
+        let mut rtf = br"{\rtf1 before {\bin4 ".to_vec();
+        rtf.extend_from_slice(b"}{\\x");
+        rtf.extend_from_slice(b"} after}");
+        assert_eq!(rtf_to_text(&rtf), "before  after");
+    }
+
+    #[test]
+    fn fb2_follows_the_declared_encoding() {
+        let mut fb2 = br#"<?xml version="1.0" encoding="windows-1251"?><FictionBook><body><section><p>"#.to_vec();
+        fb2.extend_from_slice(&[0xcf, 0xf0, 0xe8, 0xe2, 0xe5, 0xf2]);
+        fb2.extend_from_slice(b"</p></section></body></FictionBook>");
+        let (text, _, _) = fb2_to_text(&fb2).unwrap();
+        assert_eq!(text.trim(), "Привет");
+    }
+}
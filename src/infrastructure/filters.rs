@@ -0,0 +1,208 @@
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+// Footnote marker pattern of earlier versions, it also cut the digits of "3.14" and "1,000"
+const OLD_FOOTNOTE_PATTERN: &str = r"[¹²³⁴⁵⁶⁷⁸⁹⁰]+|[†‡]+|([.,;:!?”’)])\d{1,3}\b";
+const FOOTNOTE_PATTERN: &str = r"[¹²³⁴⁵⁶⁷⁸⁹⁰]+|[†‡]+|([A-Za-z][.,;:!?]|[)”’])\d{1,3}\b";
+
+// Regex replaced in the text after extraction and before it's split into words,
+// texts turn rules off by id so renaming a rule keeps those choices
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FilterRule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub pattern: String,
+    pub replace: String,
+    pub enabled: bool,
+}
+
+pub fn filter_presets() -> Vec<FilterRule> {
+    [
+        ("Numeric citations", r"\[\d+(?:\s*[,–-]\s*\d+)*\]", ""),
+        ("Author-year citations", r"\([A-Z][^()]{0,80}?,?\s\d{4}[a-z]?\)", ""),
+        ("URLs", r"https?://\S+|www\.\S+", ""),
+        ("DOIs", r"(?:doi:\s*|https?://doi\.org/)?\b10\.\d{4,9}/\S+", ""),
+        ("Emails", r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+", ""),
+        ("Footnote markers", FOOTNOTE_PATTERN, "$1"),
+    ]
+    .into_iter()
+    .map(|(name, pattern, replace)| FilterRule {
+        id: name.to_string(),
+        name: name.to_string(),
+        pattern: pattern.to_string(),
+        replace: replace.to_string(),
+        enabled: false,
+    })
+    .collect()
+}
+
+// Rules saved before ids existed were turned off by name, so the name becomes their id
+pub fn upgrade_filters(rules: &mut [FilterRule]) {
+    for rule in rules {
+        if rule.id.is_empty() {
+            rule.id = rule.name.clone();
+        }
+        if rule.pattern == OLD_FOOTNOTE_PATTERN {
+            rule.pattern = FOOTNOTE_PATTERN.to_string();
+        }
+    }
+}
+
+pub fn new_filter_id(rules: &[FilterRule]) -> String {
+    (1..)
+    .map(|n| format!("rule-{}", n))
+    .find(|id| rules.iter().all(|rule| rule.id != *id))
+    .expect("Ran out of filter ids")
+}
+
+pub fn is_valid_filter(rule: &FilterRule) -> bool {
+    Regex::new(&rule.pattern).is_ok()
+}
+
+// Enabled rules compiled once per text, the ones turned off for the document and invalid patterns are skipped
+pub fn compile_filters<'a>(rules: &'a [FilterRule], disabled: &[String]) -> Vec<(Regex, &'a str)> {
+    rules.iter()
+    .filter(|rule| rule.enabled && !disabled.contains(&rule.id))
+    .filter_map(|rule| Regex::new(&rule.pattern).ok().map(|regex| (regex, rule.replace.as_str())))
+    .collect()
+}
+
+// Same as replace_all, moving the sorted byte offsets along with the text they point at.
+// An offset inside a match ends up after its replacement
+fn replace_tracking(text: &str, regex: &Regex, replace: &str, offsets: &mut [usize]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut next = 0;
+    for captures in regex.captures_iter(text) {
+        let Some(found) = captures.get(0) else { continue };
+        while next < offsets.len() && offsets[next] <= found.start() {
+            offsets[next] = result.len() + offsets[next] - last;
+            next += 1;
+        }
+        result.push_str(&text[last..found.start()]);
+        captures.expand(replace, &mut result);
+        while next < offsets.len() && offsets[next] < found.end() {
+            offsets[next] = result.len();
+            next += 1;
+        }
+        last = found.end();
+    }
+    for offset in &mut offsets[next..] {
+        *offset = result.len() + *offset - last;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+// Runs the rules in order over the whole text so they can match across chapters, and gives the
+// word each chapter starts at from the byte offset of its first line
+pub fn filter_chapters(content: &str, starts: &[usize], filters: &[(Regex, &str)]) -> (String, Vec<usize>) {
+    let mut offsets: Vec<usize> = starts.iter()
+    .scan(0, |previous, start| {
+        *previous = (*start).clamp(*previous, content.len());
+        Some(*previous)
+    })
+    .collect();
+    let filtered = filters.iter()
+    .fold(content.to_string(), |text, (regex, replace)| replace_tracking(&text, regex, replace, &mut offsets));
+
+    let mut words = 0;
+    let mut counted = 0;
+    let word_starts = offsets.into_iter()
+    .map(|offset| {
+        let piece = &filtered[counted..offset];
+        // A word cut by the previous offset is already counted
+        let cut_word = filtered[..counted].ends_with(|c: char| !c.is_whitespace()) && piece.starts_with(|c: char| !c.is_whitespace());
+        words += piece.split_whitespace().count() - usize::from(cut_word);
+        counted = offset;
+        words
+    })
+    .collect();
+    (filtered, word_starts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(content: &str, rules: &[FilterRule], disabled: &[String]) -> String {
+        filter_chapters(content, &[], &compile_filters(rules, disabled)).0
+    }
+
+    fn preset(name: &str) -> FilterRule {
+        let mut rule = filter_presets().into_iter().find(|rule| rule.name == name).unwrap();
+        rule.enabled = true;
+        rule
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        assert!(filter_presets().iter().all(is_valid_filter));
+    }
+
+    #[test]
+    fn footnote_markers_keep_numbers() {
+        let rules = [preset("Footnote markers")];
+        assert_eq!(filter("Pi is 3.14 and 1,000 people came.", &rules, &[]), "Pi is 3.14 and 1,000 people came.");
+        assert_eq!(filter("It ended.12 Then (see this)3 and “so”4 on¹.", &rules, &[]), "It ended. Then (see this) and “so” on.");
+        assert_eq!(filter("An mp3 file", &rules, &[]), "An mp3 file");
+    }
+
+    #[test]
+    fn citations_urls_and_emails_are_removed() {
+        let rules = [preset("Numeric citations"), preset("Author-year citations"), preset("URLs"), preset("Emails")];
+        let text = "As shown [1, 2] before (Smith et al., 2019) at https://example.org by me@example.org today.";
+        assert_eq!(filter(text, &rules, &[]), "As shown  before  at  by  today.");
+    }
+
+    #[test]
+    fn rules_turned_off_by_id_survive_renaming() {
+        let mut rule = preset("URLs");
+        let disabled = vec![rule.id.clone()];
+        rule.name = "Links".to_string();
+        assert_eq!(filter("see www.example.org", &[rule], &disabled), "see www.example.org");
+    }
+
+    #[test]
+    fn old_rules_are_upgraded() {
+        let mut rules = vec![FilterRule {
+            id: String::new(),
+            name: "Footnote markers".to_string(),
+            pattern: OLD_FOOTNOTE_PATTERN.to_string(),
+            replace: "$1".to_string(),
+            enabled: true,
+        }];
+        upgrade_filters(&mut rules);
+        assert_eq!(rules[0].id, "Footnote markers");
+        assert_eq!(rules[0].pattern, FOOTNOTE_PATTERN);
+        assert_eq!(new_filter_id(&rules), "rule-1");
+    }
+
+    #[test]
+    fn invalid_and_disabled_rules_are_skipped() {
+        let invalid = FilterRule { id: "a".to_string(), name: "a".to_string(), pattern: "(".to_string(), replace: String::new(), enabled: true };
+        let mut off = preset("URLs");
+        off.enabled = false;
+        assert_eq!(filter("www.example.org", &[invalid, off], &[]), "www.example.org");
+    }
+
+    #[test]
+    fn chapter_starts_survive_filtering() {
+        let rules = [preset("URLs")];
+        let content = "Intro see www.example.org\nChapter 1 one two\nChapter 2 three";
+        let (text, starts) = filter_chapters(content, &[26, 44], &compile_filters(&rules, &[]));
+        assert_eq!(text, "Intro see \nChapter 1 one two\nChapter 2 three");
+        assert_eq!(starts, vec![2, 6]);
+        // Without rules the words before each start are counted as they are
+        assert_eq!(filter_chapters(content, &[26, 44], &[]).1, vec![3, 7]);
+    }
+
+    #[test]
+    fn rules_match_across_chapters() {
+        let rule = FilterRule { id: "a".to_string(), name: "a".to_string(), pattern: r"end\s+Chapter".to_string(), replace: "end".to_string(), enabled: true };
+        let (text, starts) = filter_chapters("the end\nChapter 2", &[8], &compile_filters(&[rule], &[]));
+        assert_eq!(text, "the end 2");
+        assert_eq!(starts, vec![2]);
+    }
+}
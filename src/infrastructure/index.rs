@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use super::paths::PathConfig;
+use super::library::library_texts;
+use crate::model::LibrarySearchResult;
+
+const MAX_RESULTS: usize = 200;
+const SNIPPET_WORDS: usize = 6;
+
+// Inverted index over the cached texts: term -> (text id, word positions)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LibraryIndex {
+    texts: Vec<String>,
+    postings: HashMap<String, Vec<(u32, Vec<u32>)>>,
+}
+
+// Lowercase with punctuation removed, so "Whale," and "whale" are the same term
+fn normalize(word: &str) -> String {
+    word.chars()
+    .filter(|c| c.is_alphanumeric())
+    .flat_map(|c| c.to_lowercase())
+    .collect()
+}
+
+impl LibraryIndex {
+    pub fn contains(&self, name: &str) -> bool {
+        self.texts.iter().any(|text| text == name)
+    }
+
+    pub fn add_text(&mut self, name: &str, content: &str) {
+        if self.contains(name) {
+            return;
+        }
+        let id = self.texts.len() as u32;
+        self.texts.push(name.to_string());
+
+        let mut positions: HashMap<String, Vec<u32>> = HashMap::new();
+        for (i, word) in content.split_whitespace().enumerate() {
+            let term = normalize(word);
+            if !term.is_empty() {
+                positions.entry(term).or_default().push(i as u32);
+            }
+        }
+        for (term, positions) in positions {
+            self.postings.entry(term).or_default().push((id, positions));
+        }
+    }
+
+    // Texts and word positions where every word of the phrase follows the previous one
+    fn find_phrase(&self, phrase: &str) -> Vec<(String, usize)> {
+        let terms: Vec<String> = phrase.split_whitespace()
+        .map(normalize)
+        .filter(|term| !term.is_empty())
+        .collect();
+        let Some(first) = terms.first().and_then(|term| self.postings.get(term)) else { return Vec::new() };
+
+        let mut matches: Vec<(String, usize)> = Vec::new();
+        for (id, positions) in first {
+            let rest: Vec<Option<&Vec<u32>>> = terms[1..].iter()
+            .map(|term| {
+                self.postings.get(term)
+                .and_then(|postings| postings.iter().find(|(other, _)| other == id))
+                .map(|(_, positions)| positions)
+            })
+            .collect();
+            if rest.iter().any(|positions| positions.is_none()) {
+                continue;
+            }
+            for position in positions {
+                let follows = rest.iter().enumerate().all(|(k, positions)| {
+                    positions.is_some_and(|p| p.binary_search(&(position + k as u32 + 1)).is_ok())
+                });
+                if follows {
+                    matches.push((self.texts[*id as usize].clone(), *position as usize));
+                }
+            }
+        }
+        matches
+    }
+
+    pub fn search(&self, path: &PathConfig, phrase: &str) -> Vec<LibrarySearchResult> {
+        let titles: HashMap<String, String> = library_texts(path).into_iter()
+        .map(|entry| (entry.name, entry.title))
+        .collect();
+        let mut contents: HashMap<String, Vec<String>> = HashMap::new();
+
+        self.find_phrase(phrase).into_iter()
+        .take(MAX_RESULTS)
+        .map(|(name, index)| {
+            let words = contents.entry(name.clone()).or_insert_with(|| {
+                std::fs::read_to_string(format!("{}texts/{}.txt", path.get_data(), name))
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(|word| word.to_string())
+                .collect()
+            });
+            let start = index.saturating_sub(SNIPPET_WORDS).min(words.len());
+            let end = (index + SNIPPET_WORDS + 1).min(words.len());
+            LibrarySearchResult {
+                title: titles.get(&name).cloned().unwrap_or_else(|| name.clone()),
+                name,
+                index,
+                snippet: words[start..end].join(" "),
+            }
+        })
+        .collect()
+    }
+}
+
+fn write_library_index(path: &PathConfig, index: &LibraryIndex) {
+    let json_string = serde_json::to_string(index)
+    .expect("Invalid JSON format");
+    let _ = std::fs::write(format!("{}index.json", path.get_data()), json_string);
+}
+
+// Run in the background, the index can be large. It is shared with
+// the app, which only copies it when changed while a save is running
+pub async fn save_library_index(path: PathConfig, index: Arc<LibraryIndex>) {
+    write_library_index(&path, &index);
+}
+
+// Run in the background too, snippets are read from the cached texts
+pub async fn search_library(path: PathConfig, index: Arc<LibraryIndex>, phrase: String) -> (String, Vec<LibrarySearchResult>) {
+    let results = index.search(&path, &phrase);
+    (phrase, results)
+}
+
+// Loads the saved index and adds the cached texts it doesn't know about yet
+pub async fn load_library_index(path: PathConfig) -> LibraryIndex {
+    let mut index: LibraryIndex = std::fs::read_to_string(format!("{}index.json", path.get_data()))
+    .ok()
+    .and_then(|content| serde_json::from_str(&content).ok())
+    .unwrap_or_default();
+
+    let mut changed = false;
+    for entry in library_texts(&path) {
+        if !index.contains(&entry.name)
+            && let Ok(content) = std::fs::read_to_string(format!("{}texts/{}.txt", path.get_data(), entry.name)) {
+                index.add_text(&entry.name, &content);
+                changed = true;
+        }
+    }
+    if changed {
+        write_library_index(&path, &index);
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> LibraryIndex {
+        let mut index = LibraryIndex::default();
+        index.add_text("moby", "Call me Ishmael. Some years ago, never mind how long precisely");
+        index.add_text("notes", "Ishmael, some notes. Years ago I read it");
+        index
+    }
+
+    #[test]
+    fn terms_ignore_case_and_punctuation() {
+        assert_eq!(normalize("Ishmael,"), "ishmael");
+        assert_eq!(normalize("“Don’t”"), "dont");
+        assert_eq!(normalize("—"), "");
+    }
+
+    #[test]
+    fn phrases_match_consecutive_words() {
+        let index = index();
+        assert_eq!(index.find_phrase("years ago"), vec![("moby".to_string(), 4), ("notes".to_string(), 3)]);
+        // Punctuation between the words doesn't matter
+        assert_eq!(index.find_phrase("Ishmael some"), vec![("moby".to_string(), 2), ("notes".to_string(), 0)]);
+        assert!(index.find_phrase("ago years").is_empty());
+        assert!(index.find_phrase("whale").is_empty());
+        assert!(index.find_phrase("...").is_empty());
+    }
+
+    #[test]
+    fn texts_are_indexed_once() {
+        let mut index = index();
+        index.add_text("moby", "years ago");
+        assert_eq!(index.find_phrase("years ago").len(), 2);
+        assert!(index.contains("notes"));
+        assert!(!index.contains("other"));
+    }
+}
@@ -0,0 +1,121 @@
+use iced::keyboard::{Key, Modifiers, key::Named};
+use derive_more::Display;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Display, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum Action {
+    #[display("Pause / resume")]
+    PlayPause,
+    #[display("Previous word")]
+    PreviousWord,
+    #[display("Next word")]
+    NextWord,
+    #[display("Read faster")]
+    Faster,
+    #[display("Read slower")]
+    Slower,
+    #[display("Toggle fullscreen")]
+    ToggleFullscreen,
+    #[display("Exit fullscreen")]
+    ExitFullscreen,
+    #[display("Read clipboard")]
+    ReadClipboard,
+    #[display("Search")]
+    Search,
+    #[display("Add bookmark")]
+    AddBookmark,
+    #[display("Highlight sentence")]
+    AddHighlight,
+}
+
+impl Action {
+    // Actions that keep going while their key is held down
+    pub fn repeats(&self) -> bool {
+        matches!(self, Action::PreviousWord | Action::NextWord | Action::Faster | Action::Slower)
+    }
+    // Window actions, the only ones that work away from the reader
+    pub fn works_anywhere(&self) -> bool {
+        matches!(self, Action::ToggleFullscreen | Action::ExitFullscreen)
+    }
+}
+
+// Key written as "Ctrl+Shift+F", "Space" or "ArrowLeft"
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KeyBinding {
+    pub action: Action,
+    pub key: String,
+}
+
+pub fn default_key_bindings() -> Vec<KeyBinding> {
+    [
+        (Action::PlayPause, "Space"),
+        (Action::PreviousWord, "ArrowLeft"),
+        (Action::NextWord, "ArrowRight"),
+        (Action::Faster, "ArrowUp"),
+        (Action::Slower, "ArrowDown"),
+        (Action::ToggleFullscreen, "F11"),
+        (Action::ExitFullscreen, "Escape"),
+        (Action::ReadClipboard, "Ctrl+V"),
+        (Action::Search, "Ctrl+F"),
+        (Action::AddBookmark, "B"),
+        (Action::AddHighlight, "H"),
+    ]
+    .into_iter()
+    .map(|(action, key)| KeyBinding { action, key: key.to_string() })
+    .collect()
+}
+
+// Same format as the bindings, None for modifier keys pressed alone
+pub fn key_spec(key: &Key, modifiers: Modifiers) -> Option<String> {
+    let name = match key {
+        Key::Named(Named::Control | Named::Shift | Named::Alt | Named::Super | Named::Meta) => return None,
+        Key::Named(named) => format!("{:?}", named),
+        Key::Character(c) => c.to_uppercase(),
+        Key::Unidentified => return None,
+    };
+    let mut spec = String::new();
+    if modifiers.command() {
+        spec.push_str("Ctrl+");
+    }
+    if modifiers.alt() {
+        spec.push_str("Alt+");
+    }
+    if modifiers.shift() {
+        spec.push_str("Shift+");
+    }
+    spec.push_str(&name);
+    Some(spec)
+}
+
+// Action other than the one at index that already uses the key
+pub fn key_conflict(bindings: &[KeyBinding], index: usize, key: &str) -> Option<Action> {
+    bindings.iter().enumerate()
+    .find(|(i, binding)| *i != index && binding.key == key)
+    .map(|(_, binding)| binding.action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_spec_matches_the_binding_format() {
+        assert_eq!(key_spec(&Key::Character("f".into()), Modifiers::COMMAND).as_deref(), Some("Ctrl+F"));
+        assert_eq!(key_spec(&Key::Named(Named::Space), Modifiers::SHIFT).as_deref(), Some("Shift+Space"));
+        assert_eq!(key_spec(&Key::Named(Named::Shift), Modifiers::SHIFT), None);
+    }
+
+    #[test]
+    fn only_window_actions_work_anywhere() {
+        assert!(Action::ExitFullscreen.works_anywhere());
+        assert!(!Action::PlayPause.works_anywhere());
+        assert!(!Action::AddHighlight.works_anywhere());
+    }
+
+    #[test]
+    fn conflicts_skip_the_edited_binding() {
+        let bindings = default_key_bindings();
+        assert_eq!(key_conflict(&bindings, 0, &bindings[0].key), None);
+        assert_eq!(key_conflict(&bindings, 1, &bindings[0].key), Some(bindings[0].action));
+    }
+}
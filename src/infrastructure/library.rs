@@ -0,0 +1,138 @@
+use super::paths::PathConfig;
+use super::config::{file_processor, get_text_meta, Preprocess};
+use super::archives::{archive_entries, is_archive, is_readable};
+use crate::model::LibraryEntry;
+
+// Every text saved in the cache, sorted by title
+pub fn library_texts(path: &PathConfig) -> Vec<LibraryEntry> {
+    let Ok(files) = std::fs::read_dir(format!("{}texts/", path.get_data())) else { return Vec::new() };
+    let mut entries: Vec<LibraryEntry> = files
+    .flatten()
+    .filter_map(|file| {
+        let file_path = file.path();
+        if file_path.extension().and_then(|e| e.to_str()) != Some("txt") {
+            return None;
+        }
+        let name = file_path.file_stem()?.to_str()?.to_string();
+        let meta = get_text_meta(path, &name);
+        let title = if meta.title.is_empty() { name.clone() } else { meta.title };
+        Some(LibraryEntry { name, title })
+    })
+    .collect();
+    entries.sort_by_key(|entry| entry.title.to_lowercase());
+    entries
+}
+
+// Size and modification time, a file still being copied changes between two looks
+pub type FileStamp = (u64, std::time::SystemTime);
+
+fn file_stamp(file_path: &std::path::Path) -> Option<FileStamp> {
+    let metadata = std::fs::metadata(file_path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+// Inbox files as they were when imported, kept in inbox.json so a restart doesn't import them again
+pub type InboxSeen = std::collections::HashMap<std::path::PathBuf, FileStamp>;
+
+pub fn load_inbox_seen(path: &PathConfig) -> InboxSeen {
+    std::fs::read_to_string(format!("{}inbox.json", path.get_data()))
+    .ok()
+    .and_then(|content| serde_json::from_str(&content).ok())
+    .unwrap_or_default()
+}
+
+pub fn save_inbox_seen(path: &PathConfig, seen: &InboxSeen) {
+    let json_string = serde_json::to_string(seen)
+    .expect("Failed to serialize inbox");
+    let _ = std::fs::write(format!("{}inbox.json", path.get_data()), json_string);
+}
+
+// Files of the inbox folder that can be imported
+fn inbox_files(inbox: &str) -> Vec<std::path::PathBuf> {
+    let Ok(files) = std::fs::read_dir(inbox) else { return Vec::new() };
+    files
+    .flatten()
+    .map(|file| file.path())
+    .filter(|file_path| {
+        let filename = file_path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+        file_path.is_file() && (is_readable(filename) || is_archive(filename))
+    })
+    .collect()
+}
+
+// Extracts a file, or every readable file of an archive, into the cache,
+// None when the file couldn't be read
+pub async fn import_file(path: PathConfig, file_path: std::path::PathBuf, preprocess: Preprocess) -> Option<Vec<String>> {
+    let data = std::fs::read(&file_path).ok()?;
+    let filename = file_path.file_name()
+    .and_then(|s| s.to_str())
+    .unwrap_or_default()
+    .to_string();
+
+    let files = if is_archive(&filename) {
+        archive_entries(data, filename).await
+        .into_iter()
+        .map(|entry| (entry.name.rsplit('/').next().unwrap_or(&entry.name).to_string(), entry.data))
+        .collect()
+    } else {
+        vec![(filename, data)]
+    };
+
+    let mut imported: Vec<String> = Vec::new();
+    for (filename, data) in files {
+        if let Some(loaded) = file_processor(Default::default(), path.clone(), data, filename, preprocess).await {
+            imported.push(loaded.name);
+        }
+    }
+    Some(imported)
+}
+
+// Looks at the inbox away from the UI thread, every importable file with its stamp
+pub async fn scan_inbox(inbox: String) -> (String, Vec<(std::path::PathBuf, FileStamp)>) {
+    let files = inbox_files(&inbox)
+    .into_iter()
+    .filter_map(|file_path| {
+        let stamp = file_stamp(&file_path)?;
+        Some((file_path, stamp))
+    })
+    .collect();
+    (inbox, files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seen_inbox_files_survive_a_restart() {
+        let dir = std::env::temp_dir().join(format!("rsvp-inbox-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut path = PathConfig::default();
+        path.set_data(format!("{}/", dir.display()));
+        assert!(load_inbox_seen(&path).is_empty());
+
+        let book = dir.join("book.txt");
+        std::fs::write(&book, "Call me Ishmael.").unwrap();
+        let stamp = file_stamp(&book).unwrap();
+        let mut seen = InboxSeen::new();
+        seen.insert(book.clone(), stamp);
+        save_inbox_seen(&path, &seen);
+        assert_eq!(load_inbox_seen(&path).get(&book), Some(&stamp));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn scan_lists_only_importable_files() {
+        let dir = std::env::temp_dir().join(format!("rsvp-scan-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("book.txt"), "Call me Ishmael.").unwrap();
+        std::fs::write(dir.join("cover.png"), [0u8; 4]).unwrap();
+        let inbox = dir.display().to_string();
+        let (scanned, files) = iced::futures::executor::block_on(scan_inbox(inbox.clone()));
+        assert_eq!(scanned, inbox);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, dir.join("book.txt"));
+        assert_eq!(files[0].1.0, 16);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -1,13 +1,93 @@
+pub mod archives;
 pub mod config;
+pub mod contents;
+pub mod export;
+pub mod extractors;
+pub mod filters;
+pub mod index;
+pub mod keys;
+pub mod library;
 pub mod paths;
+pub mod preprocess;
+pub mod quiz;
+pub mod search;
+pub mod stats;
+pub mod training;
 
 pub use config::get_config_path;
 pub use config::Config;
 pub use config::ColorTheme;
+pub use config::NamedTheme;
+pub use config::ThemeMode;
+pub use config::AutoTheme;
+pub use config::DayTime;
+pub use config::parse_theme_file;
+pub use config::theme_file;
+pub use config::open_theme_dialog;
+pub use config::GoalKind;
+pub use config::ReadingGoal;
+pub use config::QuizSettings;
+pub use config::TrainingSettings;
+pub use config::WarmupSettings;
+pub use config::PauseMode;
+pub use config::ScrollMode;
+pub use config::BoundaryPause;
+pub use config::AutoPauseSettings;
 pub use config::get_config_file;
 pub use config::save_config_file;
 pub use config::open_file_dialog;
 pub use config::open_file_dialog_font;
 pub use config::get_font;
+pub use config::load_font_file;
+pub use config::FONT_EXTENSIONS;
 pub use config::file_processor;
+pub use config::save_text;
+pub use config::unused_text_name;
+pub use config::save_text_meta;
+pub use archives::archive_entries;
+pub use archives::is_archive;
+pub use archives::is_readable;
 
+pub use config::open_folder_dialog;
+pub use library::library_texts;
+pub use library::scan_inbox;
+pub use library::InboxSeen;
+pub use library::load_inbox_seen;
+pub use library::save_inbox_seen;
+pub use library::import_file;
+pub use library::FileStamp;
+pub use filters::FilterRule;
+pub use filters::filter_presets;
+pub use filters::compile_filters;
+pub use filters::filter_chapters;
+pub use filters::new_filter_id;
+pub use filters::is_valid_filter;
+pub use config::save_file_dialog;
+pub use export::highlights_to_markdown;
+pub use export::highlights_to_json;
+pub use search::search_words;
+pub use search::SearchDocument;
+pub use index::LibraryIndex;
+pub use index::load_library_index;
+pub use index::save_library_index;
+pub use index::search_library;
+pub use contents::detect_headings;
+pub use stats::load_sessions;
+pub use stats::save_session;
+pub use stats::finish_session;
+pub use stats::daily_totals;
+pub use stats::today_total;
+pub use stats::weekly_totals;
+pub use stats::reading_streaks;
+pub use stats::session_wpm;
+pub use quiz::make_quiz;
+pub use training::training_wpm;
+pub use training::next_baseline;
+pub use training::MIN_WPM;
+pub use training::MAX_WPM;
+pub use contents::text_boundaries;
+pub use keys::Action;
+pub use keys::KeyBinding;
+pub use keys::default_key_bindings;
+pub use keys::key_spec;
+pub use keys::key_conflict;
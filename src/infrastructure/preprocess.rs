@@ -0,0 +1,248 @@
+use crate::model::StrippedSection;
+
+const COPYRIGHT_KEYWORDS: [&str; 7] = [
+    "copyright", "©", "all rights reserved", "isbn", "published by", "printed in", "first edition",
+];
+const CONTENTS_HEADINGS: [&str; 3] = ["contents", "table of contents", "index of chapters"];
+const BACK_MATTER_HEADINGS: [&str; 9] = [
+    "the end", "about the author", "about the authors", "acknowledgments", "acknowledgements",
+    "also by", "glossary", "index", "endnotes",
+];
+
+// Removes Project Gutenberg headers and license, copyright pages and tables of contents,
+// and back matter when asked. Returns the text to read and what was taken out of it.
+pub fn strip_boilerplate(content: &str, back_matter: bool) -> (String, Vec<StrippedSection>) {
+    let mut stripped: Vec<StrippedSection> = Vec::new();
+    let mut body = content;
+
+    if let Some(start) = find_line(body, |l| is_gutenberg_marker(l, "START")) {
+        let end = line_end(body, start);
+        stripped.push(section("Project Gutenberg header", &body[..end]));
+        body = &body[end..];
+    }
+    if let Some(start) = find_line(body, |l| is_gutenberg_marker(l, "END")) {
+        stripped.push(section("Project Gutenberg license", &body[start..]));
+        body = &body[..start];
+    }
+
+    let lines = lines_with_offsets(body);
+    // Front matter is only looked for before the first paragraph of prose,
+    // and never further than the first 600 lines
+    let limit = lines.get(600).map(|l| l.0).unwrap_or(body.len());
+    let front_end = paragraphs(&lines).into_iter()
+    .find(|(start, end)| is_prose(&body[*start..*end]))
+    .map(|(start, _)| start.min(limit))
+    .unwrap_or(0);
+    let mut ranges: Vec<(&str, usize, usize)> = Vec::new();
+
+    for (start, end) in paragraphs(&lines) {
+        if start >= front_end {
+            break;
+        }
+        if is_copyright(&body[start..end]) {
+            ranges.push(("Copyright page", start, end));
+        }
+    }
+
+    if let Some(range) = contents_range(body, &lines, front_end) {
+        ranges.push(("Table of contents", range.0, range.1));
+    }
+
+    if back_matter {
+        let back_start = lines.get(lines.len() * 4 / 5).map(|l| l.0).unwrap_or(body.len());
+        let marker = lines.iter()
+        .find(|(offset, line)| *offset >= back_start && BACK_MATTER_HEADINGS.contains(&normalize(line).as_str()));
+        if let Some((offset, line)) = marker {
+            // "THE END" belongs to the story, what follows doesn't
+            let start = if normalize(line) == "the end" { line_end(body, *offset) } else { *offset };
+            ranges.push(("Back matter", start, body.len()));
+        }
+    }
+
+    // Overlapping ranges are merged into the first one
+    ranges.sort_by_key(|r| r.1);
+    let mut result = String::new();
+    let mut position = 0;
+    for (label, start, end) in ranges {
+        if end <= position {
+            continue;
+        }
+        let start = start.max(position);
+        result.push_str(&body[position..start]);
+        stripped.push(section(label, &body[start..end]));
+        position = end;
+    }
+    result.push_str(&body[position..]);
+
+    (result.trim().to_string(), stripped)
+}
+
+fn section(label: &str, text: &str) -> StrippedSection {
+    StrippedSection { label: label.to_string(), text: text.trim().to_string() }
+}
+
+// Short paragraph with several copyright keywords, or opening with one
+fn is_copyright(paragraph: &str) -> bool {
+    let paragraph = paragraph.trim().to_lowercase();
+    let keywords = COPYRIGHT_KEYWORDS.iter().filter(|k| paragraph.contains(*k)).count();
+    paragraph.split_whitespace().count() <= 80
+    && (keywords >= 2 || COPYRIGHT_KEYWORDS.iter().any(|k| paragraph.starts_with(k)))
+}
+
+// A paragraph of at least 40 words that isn't a copyright notice
+fn is_prose(paragraph: &str) -> bool {
+    paragraph.split_whitespace().count() >= 40 && !is_copyright(paragraph)
+}
+
+fn is_gutenberg_marker(line: &str, kind: &str) -> bool {
+    let line = line.trim_start_matches(['*', ' ']).to_uppercase();
+    line.starts_with(&format!("{} OF THE PROJECT GUTENBERG", kind))
+    || line.starts_with(&format!("{} OF THIS PROJECT GUTENBERG", kind))
+}
+
+fn lines_with_offsets(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    text.split_inclusive('\n')
+    .map(|line| {
+        let current = offset;
+        offset += line.len();
+        (current, line.trim_end())
+    })
+    .collect()
+}
+
+fn find_line(text: &str, predicate: impl Fn(&str) -> bool) -> Option<usize> {
+    lines_with_offsets(text).into_iter()
+    .find(|(_, line)| predicate(line))
+    .map(|(offset, _)| offset)
+}
+
+fn line_end(text: &str, start: usize) -> usize {
+    text[start..].find('\n').map(|i| start + i + 1).unwrap_or(text.len())
+}
+
+// Byte ranges of blocks of consecutive non blank lines
+fn paragraphs(lines: &[(usize, &str)]) -> Vec<(usize, usize)> {
+    let mut result: Vec<(usize, usize)> = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    for (offset, line) in lines {
+        if line.trim().is_empty() {
+            result.extend(current.take());
+        } else {
+            let end = offset + line.len();
+            current = Some((current.map(|c| c.0).unwrap_or(*offset), end));
+        }
+    }
+    result.extend(current);
+    result
+}
+
+// Lowercase, without page numbers or dot leaders
+fn normalize(line: &str) -> String {
+    line.trim()
+    .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace())
+    .to_lowercase()
+}
+
+// Lowercase title of a contents entry, without the page number when dot leaders
+// or a wide gap set it apart, so "Chapter 1" and "Chapter 2" stay different
+fn entry_title(line: &str) -> String {
+    let line = line.trim();
+    let without_page = line.trim_end_matches(|c: char| c.is_ascii_digit());
+    let title = if without_page.len() < line.len()
+        && (without_page.ends_with("..") || without_page.ends_with(". ") || without_page.ends_with("  ") || without_page.ends_with('\t')) {
+        without_page.trim_end_matches(|c: char| c == '.' || c.is_whitespace())
+    } else {
+        line
+    };
+    title.to_lowercase()
+}
+
+// From the contents heading to the first chapter, found by its title being repeated
+fn contents_range(body: &str, lines: &[(usize, &str)], front_end: usize) -> Option<(usize, usize)> {
+    let heading = lines.iter()
+    .position(|(offset, line)| *offset < front_end && CONTENTS_HEADINGS.contains(&normalize(line).as_str()))?;
+    let (first_entry, entry) = lines.iter().enumerate()
+    .skip(heading + 1)
+    .find(|(_, (_, line))| !line.trim().is_empty())?;
+    let entry = entry_title(entry.1);
+
+    if let Some((offset, _)) = lines[first_entry + 1..].iter().find(|(_, line)| entry_title(line) == entry) {
+        return Some((lines[heading].0, *offset));
+    }
+
+    // Without a repeated title, entries end with the first line of prose and the
+    // paragraph right before it is kept as the chapter heading
+    let prose = lines[first_entry..].iter().position(|(_, line)| line.trim().len() > 70)? + first_entry;
+    let chapter = paragraphs(&lines[first_entry..prose]).last().map(|p| p.0)?;
+    (chapter > lines[heading].0).then_some((lines[heading].0, chapter.min(body.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prose(words: usize) -> String {
+        vec!["word"; words].join(" ")
+    }
+
+    #[test]
+    fn short_text_mentioning_copyright_is_kept() {
+        let text = "The copyright of the song was all he owned, and he held on to it.\n\nHe never sold it.";
+        let (result, stripped) = strip_boilerplate(text, false);
+        assert_eq!(result, text);
+        assert!(stripped.is_empty());
+    }
+
+    #[test]
+    fn prose_after_the_first_body_paragraph_is_kept() {
+        let text = format!("Title\n\nCopyright © 1990 Someone\nAll rights reserved\n\n{}\n\nCopyright laws differ, she said.", prose(50));
+        let (result, stripped) = strip_boilerplate(&text, false);
+        assert_eq!(stripped.len(), 1);
+        assert_eq!(stripped[0].label, "Copyright page");
+        assert!(result.starts_with("Title"));
+        assert!(result.ends_with("Copyright laws differ, she said."));
+    }
+
+    #[test]
+    fn long_paragraph_with_keywords_is_not_a_copyright_page() {
+        let text = format!("Copyright and ISBN are mentioned here. {}", prose(100));
+        let (result, stripped) = strip_boilerplate(&text, false);
+        assert_eq!(result, text);
+        assert!(stripped.is_empty());
+    }
+
+    #[test]
+    fn gutenberg_header_and_license_are_removed() {
+        let text = format!("Header\n*** START OF THE PROJECT GUTENBERG EBOOK X ***\n{}\n*** END OF THE PROJECT GUTENBERG EBOOK X ***\nLicense", prose(50));
+        let (result, stripped) = strip_boilerplate(&text, false);
+        assert_eq!(result, prose(50));
+        assert_eq!(stripped.len(), 2);
+    }
+
+    #[test]
+    fn whole_numbered_contents_is_removed() {
+        let text = format!("Contents\n\nChapter 1\nChapter 2\nChapter 3\n\nChapter 1\n\n{}", prose(50));
+        let (result, stripped) = strip_boilerplate(&text, false);
+        assert_eq!(stripped[0].label, "Table of contents");
+        assert!(stripped[0].text.contains("Chapter 3"));
+        assert!(result.starts_with("Chapter 1\n\nword"));
+    }
+
+    #[test]
+    fn contents_page_numbers_are_ignored() {
+        assert_eq!(entry_title("Chapter 1 ........ 5"), "chapter 1");
+        assert_eq!(entry_title("The Storm    12"), "the storm");
+        assert_eq!(entry_title("Chapter 12"), "chapter 12");
+    }
+
+    #[test]
+    fn back_matter_only_when_asked() {
+        let text = format!("{}\n\nTHE END\n\nAbout the author\nBorn somewhere.", prose(50));
+        let (kept, _) = strip_boilerplate(&text, false);
+        assert_eq!(kept, text);
+        let (result, stripped) = strip_boilerplate(&text, true);
+        assert!(result.ends_with("THE END"));
+        assert_eq!(stripped[0].label, "Back matter");
+    }
+}
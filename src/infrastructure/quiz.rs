@@ -0,0 +1,143 @@
+use crate::model::Quiz;
+
+const STOPWORDS: [&str; 40] = [
+    "that", "this", "with", "from", "have", "were", "they", "them", "their", "there",
+    "then", "than", "what", "when", "where", "which", "while", "would", "could", "should",
+    "been", "into", "only", "some", "such", "very", "will", "your", "about", "after",
+    "before", "these", "those", "other", "said", "also", "just", "like", "more", "most",
+];
+const DETERMINERS: [&str; 12] = ["the", "a", "an", "this", "that", "his", "her", "their", "its", "my", "our", "your"];
+const CHOICES: usize = 4;
+// Random words looked at when picking wrong answers
+const DISTRACTOR_TRIES: usize = 500;
+
+fn clean(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+}
+
+fn ends_sentence(word: &str) -> bool {
+    word.trim_end_matches(['"', '\'', '”', '’', ')']).ends_with(['.', '!', '?'])
+}
+
+// How likely a word is a key noun, None when it can't be asked about
+fn score(word: &str, previous: Option<&str>) -> Option<usize> {
+    let letters = word.chars().count();
+    if letters < 4 || !word.chars().all(char::is_alphabetic) || STOPWORDS.contains(&word.to_lowercase().as_str()) {
+        return None;
+    }
+    // Capitalized words inside a sentence are usually names
+    let proper = previous.is_some() && word.chars().next().is_some_and(char::is_uppercase);
+    let after_determiner = previous.is_some_and(|p| DETERMINERS.contains(&clean(p).to_lowercase().as_str()));
+    Some(letters + if proper { 10 } else { 0 } + if after_determiner { 5 } else { 0 })
+}
+
+// Cloze question on one of the last sentences of the given range: its key word
+// is blanked out and mixed with other words of the text
+pub fn make_quiz(text: &[Box<str>], start: usize, end: usize) -> Option<Quiz> {
+    let recent = text.get(start..end)?;
+    let mut sentences: Vec<(usize, usize)> = Vec::new();
+    let mut sentence_start = 0;
+    for (i, word) in recent.iter().enumerate() {
+        if ends_sentence(word) {
+            sentences.push((sentence_start, i + 1));
+            sentence_start = i + 1;
+        }
+    }
+
+    for (first, last) in sentences.into_iter().rev().take(5) {
+        if !(5..=40).contains(&(last - first)) {
+            continue;
+        }
+        let best = (first..last)
+        .filter_map(|i| {
+            let previous = (i > first).then(|| recent[i - 1].as_ref());
+            score(clean(&recent[i]), previous).map(|score| (score, i))
+        })
+        .max_by_key(|(score, _)| *score);
+        let Some((_, blank)) = best else { continue };
+        let answer = clean(&recent[blank]).to_string();
+
+        let mut choices = distractors(text, &answer, &recent[first..last]);
+        if choices.len() < CHOICES - 1 {
+            continue;
+        }
+        let sentence = recent[first..last].iter().enumerate()
+        .map(|(i, word)| if first + i == blank { word.replace(&answer, "_____") } else { word.to_string() })
+        .collect::<Vec<String>>()
+        .join(" ");
+        let position = fastrand::usize(..=choices.len());
+        choices.insert(position, answer);
+        return Some(Quiz { sentence, choices, answer: position });
+    }
+    None
+}
+
+// Words of the text that could pass for the answer: same capitalization, similar length,
+// and not already in the question
+fn distractors(text: &[Box<str>], answer: &str, sentence: &[Box<str>]) -> Vec<String> {
+    let capitalized = |word: &str| word.chars().next().is_some_and(char::is_uppercase);
+    let length = answer.chars().count();
+    let mut result: Vec<String> = Vec::new();
+    if text.is_empty() {
+        return result;
+    }
+    for _ in 0..DISTRACTOR_TRIES {
+        let i = fastrand::usize(..text.len());
+        let word = clean(&text[i]);
+        let previous = (i > 0).then(|| text[i - 1].as_ref());
+        let similar = score(word, previous).is_some()
+        && capitalized(word) == capitalized(answer)
+        && word.chars().count().abs_diff(length) <= 3;
+        let taken = word.eq_ignore_ascii_case(answer)
+        || result.iter().any(|r| r.eq_ignore_ascii_case(word))
+        || sentence.iter().any(|w| clean(w).eq_ignore_ascii_case(word));
+        if similar && !taken {
+            result.push(word.to_string());
+            if result.len() == CHOICES - 1 {
+                break;
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(content: &str) -> Vec<Box<str>> {
+        content.split_whitespace().map(Into::into).collect()
+    }
+
+    #[test]
+    fn short_sentences_are_not_asked_about() {
+        let text = words("Go now. Run fast. Stop it here.");
+        assert!(make_quiz(&text, 0, text.len()).is_none());
+    }
+
+    #[test]
+    fn too_few_distractors_means_no_question() {
+        let text = words("Tiny cats nap. The captain steered the enormous whaling vessel carefully.");
+        assert!(make_quiz(&text, 0, text.len()).is_none());
+    }
+
+    #[test]
+    fn answer_is_among_the_choices_once() {
+        let filler = "Morning brought bright weather over quiet harbour islands. ".repeat(20);
+        let text = words(&format!("{}The captain steered the enormous whaling vessel carefully.", filler));
+        let quiz = make_quiz(&text, 0, text.len()).unwrap();
+        assert_eq!(quiz.sentence, "The captain steered the _____ whaling vessel carefully.");
+        assert_eq!(quiz.choices.len(), CHOICES);
+        assert_eq!(quiz.choices[quiz.answer], "enormous");
+        assert_eq!(quiz.choices.iter().filter(|choice| choice.eq_ignore_ascii_case("enormous")).count(), 1);
+        // Wrong answers never come from the question itself
+        assert!(quiz.choices.iter().all(|choice| choice == "enormous" || !quiz.sentence.contains(choice.as_str())));
+    }
+
+    #[test]
+    fn out_of_range_is_none() {
+        let text = words("One two three.");
+        assert!(make_quiz(&text, 2, 10).is_none());
+        assert!(distractors(&[], "word", &[]).is_empty());
+    }
+}
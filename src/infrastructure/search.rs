@@ -0,0 +1,84 @@
+use regex::RegexBuilder;
+use crate::model::SearchResult;
+
+const MAX_RESULTS: usize = 500;
+const SNIPPET_WORDS: usize = 6;
+
+// Words of a text joined by single spaces, with the byte offset where each one
+// starts. Built once per loaded text so searching as you type stays cheap
+#[derive(Debug, Default)]
+pub struct SearchDocument {
+    joined: String,
+    offsets: Vec<usize>,
+}
+
+impl SearchDocument {
+    pub fn new(text: &[Box<str>]) -> Self {
+        let mut joined = String::new();
+        let mut offsets: Vec<usize> = Vec::with_capacity(text.len());
+        for word in text {
+            offsets.push(joined.len());
+            joined.push_str(word);
+            joined.push(' ');
+        }
+        SearchDocument { joined, offsets }
+    }
+}
+
+// Finds the query in the words of a text, phrases can span several words.
+// Plain queries are escaped so both modes share the same matcher.
+pub fn search_words(text: &[Box<str>], document: &SearchDocument, query: &str, regex: bool, case_insensitive: bool) -> Result<Vec<SearchResult>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let pattern = if regex { query.to_string() } else { regex::escape(query.trim()) };
+    let matcher = RegexBuilder::new(&pattern)
+    .case_insensitive(case_insensitive)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+    let results = matcher.find_iter(&document.joined)
+    .filter(|m| !m.is_empty())
+    .map(|m| document.offsets.partition_point(|offset| *offset <= m.start()) - 1)
+    // A word matched twice is listed once
+    .fold(Vec::<usize>::new(), |mut indexes, index| {
+        if indexes.last() != Some(&index) {
+            indexes.push(index);
+        }
+        indexes
+    })
+    .into_iter()
+    .take(MAX_RESULTS)
+    .map(|index| {
+        let start = index.saturating_sub(SNIPPET_WORDS);
+        let end = (index + SNIPPET_WORDS + 1).min(text.len());
+        SearchResult { index, snippet: text[start..end].join(" ") }
+    })
+    .collect();
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(content: &str) -> Vec<Box<str>> {
+        content.split_whitespace().map(Into::into).collect()
+    }
+
+    #[test]
+    fn phrases_span_words() {
+        let text = words("The quick brown fox jumps over the lazy dog");
+        let document = SearchDocument::new(&text);
+        let results = search_words(&text, &document, "brown fox", false, true).unwrap();
+        assert_eq!(results.iter().map(|r| r.index).collect::<Vec<_>>(), vec![2]);
+        let results = search_words(&text, &document, "the", false, true).unwrap();
+        assert_eq!(results.iter().map(|r| r.index).collect::<Vec<_>>(), vec![0, 6]);
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        let text = words("some words");
+        assert!(search_words(&text, &SearchDocument::new(&text), "(", true, false).is_err());
+    }
+}
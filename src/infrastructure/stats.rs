@@ -0,0 +1,222 @@
+use chrono::{Datelike, Days, Local, NaiveDate, TimeZone};
+use super::paths::PathConfig;
+use crate::model::{ReadingSession, PeriodTotal};
+
+// Finished sessions are in sessions.json, the running one is kept in its own
+// small file so saving it while reading doesn't rewrite the whole history
+fn load_history(path: &PathConfig) -> Vec<ReadingSession> {
+    std::fs::read_to_string(format!("{}sessions.json", path.get_data()))
+    .ok()
+    .and_then(|content| serde_json::from_str(&content).ok())
+    .unwrap_or_default()
+}
+
+fn load_running(path: &PathConfig) -> Option<ReadingSession> {
+    std::fs::read_to_string(format!("{}running_session.json", path.get_data()))
+    .ok()
+    .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+fn is_same_session(a: &ReadingSession, b: &ReadingSession) -> bool {
+    a.text == b.text && a.start == b.start
+}
+
+// Adds the session, or updates it when it was already saved
+fn merge_session(sessions: &mut Vec<ReadingSession>, session: &ReadingSession) {
+    match sessions.iter_mut().rev().find(|s| is_same_session(s, session)) {
+        Some(saved) => *saved = session.clone(),
+        None => sessions.push(session.clone()),
+    }
+}
+
+fn archive_session(path: &PathConfig, session: &ReadingSession) {
+    let mut sessions = load_history(path);
+    merge_session(&mut sessions, session);
+    let json_string = serde_json::to_string(&sessions)
+    .expect("Invalid JSON format");
+    let _ = std::fs::write(format!("{}sessions.json", path.get_data()), json_string);
+}
+
+// Every session, the running one included
+pub fn load_sessions(path: &PathConfig) -> Vec<ReadingSession> {
+    let mut sessions = load_history(path);
+    if let Some(running) = load_running(path) {
+        merge_session(&mut sessions, &running);
+    }
+    sessions
+}
+
+// Saves the running session, a session left running before goes to the history first
+pub fn save_session(path: &PathConfig, session: &ReadingSession) {
+    if let Some(running) = load_running(path) && !is_same_session(&running, session) {
+        archive_session(path, &running);
+    }
+    let json_string = serde_json::to_string(session)
+    .expect("Invalid JSON format");
+    let _ = std::fs::write(format!("{}running_session.json", path.get_data()), json_string);
+}
+
+// Moves the finished session into the history
+pub fn finish_session(path: &PathConfig, session: &ReadingSession) {
+    archive_session(path, session);
+    let _ = std::fs::remove_file(format!("{}running_session.json", path.get_data()));
+}
+
+// Effective speed, only the time spent reading counts
+pub fn session_wpm(session: &ReadingSession) -> f32 {
+    if session.reading_ms == 0 {
+        return 0.0;
+    }
+    session.words as f32 * 60_000.0 / session.reading_ms as f32
+}
+
+fn session_day(session: &ReadingSession) -> NaiveDate {
+    Local.timestamp_opt(session.start, 0)
+    .earliest()
+    .map(|time| time.date_naive())
+    .unwrap_or_default()
+}
+
+fn total(label: String, sessions: &[&ReadingSession]) -> PeriodTotal {
+    PeriodTotal {
+        label,
+        words: sessions.iter().map(|s| s.words).sum(),
+        reading_ms: sessions.iter().map(|s| s.reading_ms).sum(),
+    }
+}
+
+// One total per day, the last one is today
+pub fn daily_totals(sessions: &[ReadingSession], days: u64) -> Vec<PeriodTotal> {
+    let today = Local::now().date_naive();
+    (0..days).rev()
+    .filter_map(|back| today.checked_sub_days(Days::new(back)))
+    .map(|day| {
+        let of_day: Vec<&ReadingSession> = sessions.iter().filter(|s| session_day(s) == day).collect();
+        total(day.format("%d/%m").to_string(), &of_day)
+    })
+    .collect()
+}
+
+pub fn today_total(sessions: &[ReadingSession]) -> PeriodTotal {
+    daily_totals(sessions, 1).remove(0)
+}
+
+// One total per week starting on Monday, the last one is the current week
+pub fn weekly_totals(sessions: &[ReadingSession], weeks: u64) -> Vec<PeriodTotal> {
+    let today = Local::now().date_naive();
+    let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+    (0..weeks).rev()
+    .filter_map(|back| monday.checked_sub_days(Days::new(back * 7)))
+    .map(|start| {
+        let end = start + Days::new(7);
+        let of_week: Vec<&ReadingSession> = sessions.iter()
+        .filter(|s| (start..end).contains(&session_day(s)))
+        .collect();
+        total(start.format("%d/%m").to_string(), &of_week)
+    })
+    .collect()
+}
+
+// Current and longest runs of consecutive days with some reading, a streak
+// isn't broken until today is over
+pub fn reading_streaks(sessions: &[ReadingSession]) -> (usize, usize) {
+    let mut days: Vec<NaiveDate> = sessions.iter()
+    .filter(|s| s.words > 0)
+    .map(session_day)
+    .collect();
+    days.sort();
+    days.dedup();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in &days {
+        run = if previous.and_then(|p| p.succ_opt()) == Some(*day) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+
+    let today = Local::now().date_naive();
+    let yesterday = today.pred_opt().unwrap_or(today);
+    let current = if previous == Some(today) || previous == Some(yesterday) { run } else { 0 };
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A session started days_back days ago at the given local time
+    fn session(days_back: u64, hour: u32, minute: u32, words: usize) -> ReadingSession {
+        let day = Local::now().date_naive() - Days::new(days_back);
+        let start = day.and_hms_opt(hour, minute, 0).unwrap().and_local_timezone(Local).earliest().unwrap().timestamp();
+        ReadingSession {
+            text: "text".to_string(), title: "Text".to_string(), start, end: start + 1200,
+            reading_ms: 600_000, words, pauses: 0, rewinds: 0, questions: 0, correct: 0,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathConfig {
+        let dir = std::env::temp_dir().join(format!("rsvp-stats-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut path = PathConfig::default();
+        path.set_data(format!("{}/", dir.display()));
+        path
+    }
+
+    #[test]
+    fn no_sessions_means_no_streak() {
+        assert_eq!(reading_streaks(&[]), (0, 0));
+        let today = today_total(&[]);
+        assert_eq!((today.words, today.reading_ms), (0, 0));
+    }
+
+    #[test]
+    fn streaks_count_consecutive_days() {
+        let sessions = [session(6, 10, 0, 100), session(5, 10, 0, 100), session(4, 10, 0, 100), session(1, 10, 0, 100), session(1, 20, 0, 50)];
+        // Not broken before today is over
+        assert_eq!(reading_streaks(&sessions), (1, 3));
+        // A gap of one day breaks it, sessions without words don't count
+        let sessions = [session(3, 10, 0, 100), session(2, 10, 0, 0), session(0, 10, 0, 100)];
+        assert_eq!(reading_streaks(&sessions), (1, 1));
+    }
+
+    #[test]
+    fn sessions_crossing_midnight_count_on_their_start_day() {
+        let sessions = [session(1, 23, 50, 300), session(0, 9, 0, 200)];
+        let days = daily_totals(&sessions, 2);
+        assert_eq!(days.iter().map(|day| day.words).collect::<Vec<_>>(), vec![300, 200]);
+        assert_eq!(reading_streaks(&sessions), (2, 2));
+    }
+
+    #[test]
+    fn weeks_add_up_their_days() {
+        let sessions = [session(0, 9, 0, 200), session(0, 12, 0, 100), session(30, 9, 0, 1000)];
+        let weeks = weekly_totals(&sessions, 2);
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[1].words, 300);
+        assert_eq!(weeks[1].reading_ms, 1_200_000);
+    }
+
+    #[test]
+    fn running_session_moves_into_the_history() {
+        let path = temp_path("running");
+        assert!(load_sessions(&path).is_empty());
+        std::fs::write(format!("{}sessions.json", path.get_data()), "").unwrap();
+        assert!(load_sessions(&path).is_empty());
+
+        let mut first = session(0, 9, 0, 100);
+        save_session(&path, &first);
+        first.words = 200;
+        save_session(&path, &first);
+        assert_eq!(load_sessions(&path).iter().map(|s| s.words).collect::<Vec<_>>(), vec![200]);
+        // A new session sends the one left running to the history
+        let second = session(0, 11, 0, 50);
+        save_session(&path, &second);
+        finish_session(&path, &second);
+        assert_eq!(load_sessions(&path).iter().map(|s| s.words).collect::<Vec<_>>(), vec![200, 50]);
+        assert!(load_running(&path).is_none());
+        let _ = std::fs::remove_dir_all(path.get_data());
+    }
+}
@@ -0,0 +1,70 @@
+use super::config::TrainingSettings;
+use crate::model::ReadingSession;
+
+pub const MIN_WPM: u32 = 60;
+pub const MAX_WPM: u32 = 2000;
+
+// Speed after reading for reading_ms in a training session, and whether a drill sprint is on
+pub fn training_wpm(settings: &TrainingSettings, reading_ms: u64) -> (u32, bool) {
+    let seconds = reading_ms / 1000;
+    // Settings are typed freely, so the math is done in u64 and saturates
+    let ramped = (settings.baseline as u64).saturating_add((settings.ramp as u64).saturating_mul(seconds) / 60);
+    // Each drill interval ends with a sprint of drill_length seconds
+    let sprint = settings.drill_interval > 0
+    && seconds % settings.drill_interval as u64 >= settings.drill_interval.saturating_sub(settings.drill_length) as u64;
+    let wpm = if sprint { ramped.saturating_mul(settings.drill_boost as u64) / 100 } else { ramped };
+    (wpm.clamp(MIN_WPM as u64, MAX_WPM as u64) as u32, sprint)
+}
+
+// Baseline of the next session: halfway to the speed reached when it was read well,
+// 10% slower when comprehension dropped or rewinds piled up
+pub fn next_baseline(settings: &TrainingSettings, session: &ReadingSession) -> u32 {
+    let (reached, _) = training_wpm(&TrainingSettings { drill_interval: 0, ..*settings }, session.reading_ms);
+    let comprehension = (session.questions > 0).then(|| session.correct as f32 / session.questions as f32);
+    let rewind_rate = session.rewinds as f32 * 100.0 / session.words.max(1) as f32;
+
+    let struggled = comprehension.is_some_and(|c| c < 0.6) || rewind_rate > 5.0;
+    let comfortable = comprehension.is_none_or(|c| c >= 0.8) && rewind_rate < 2.0;
+    let baseline = if struggled {
+        (settings.baseline as u64 * 9 / 10) as u32
+    } else if comfortable {
+        settings.baseline + reached.saturating_sub(settings.baseline) / 2
+    } else {
+        settings.baseline
+    };
+    baseline.clamp(MIN_WPM, MAX_WPM)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_ramps_and_sprints() {
+        let settings = TrainingSettings::default();
+        assert_eq!(training_wpm(&settings, 0), (200, false));
+        assert_eq!(training_wpm(&settings, 60_000), (210, false));
+        // The last 20 seconds of every 120 are a sprint at 150%: 218 * 1.5
+        assert_eq!(training_wpm(&settings, 110_000), (327, true));
+    }
+
+    #[test]
+    fn huge_settings_do_not_overflow() {
+        let settings = TrainingSettings { enabled: true, baseline: u32::MAX, ramp: u32::MAX, drill_interval: 10, drill_length: 10, drill_boost: u32::MAX };
+        assert_eq!(training_wpm(&settings, u64::MAX), (MAX_WPM, true));
+        assert_eq!(training_wpm(&TrainingSettings { baseline: 0, ramp: 0, ..settings }, 0), (MIN_WPM, true));
+    }
+
+    #[test]
+    fn baseline_adapts_to_the_session() {
+        let settings = TrainingSettings { enabled: true, ..Default::default() };
+        let session = ReadingSession {
+            text: String::new(), title: String::new(), start: 0, end: 0,
+            reading_ms: 600_000, words: 1000, pauses: 0, rewinds: 0, questions: 0, correct: 0,
+        };
+        // 10 minutes ramp to 300 wpm, the next baseline goes halfway
+        assert_eq!(next_baseline(&settings, &session), 250);
+        let struggled = ReadingSession { questions: 5, correct: 1, ..session.clone() };
+        assert_eq!(next_baseline(&settings, &struggled), 180);
+    }
+}
@@ -3,13 +3,23 @@ mod app;
 mod message;
 mod view;
 mod style;
+mod model;
 
 use crate::app::{new, update, State, subscription};
 use view::main_view;
 use style::custom_theme_from_state;
+use std::io::Read;
 
 fn main() -> iced::Result {
-    iced::application(new, update, main_view)
+    // `rsvp -` reads the text to be shown from stdin
+    let stdin_text = std::env::args().nth(1).filter(|arg| arg == "-").map(|_| {
+        // Invalid UTF-8 is replaced rather than refused
+        let mut content = Vec::new();
+        std::io::stdin().read_to_end(&mut content).expect("Could not read stdin");
+        String::from_utf8_lossy(&content).into_owned()
+    });
+
+    iced::application(move || new(stdin_text.clone()), update, main_view)
     .theme(|state: &State| custom_theme_from_state(state.get_state_theme()))
     .font(iced_aw::ICED_AW_FONT_BYTES)
     .subscription(subscription)
@@ -1,15 +1,20 @@
 use iced::Color;
 use derive_more::Display;
 use iced::time::Instant;
+use iced::keyboard::{Key, Modifiers};
+use iced::mouse::ScrollDelta;
+use iced::theme;
+use crate::model::{LoadedText, ArchiveEntry, Boundary, Sidebar, LibrarySearchResult};
+use crate::infrastructure::{LibraryIndex, FileStamp, GoalKind, PauseMode, ScrollMode, ThemeMode, DayTime};
 
-#[derive(Debug, Display, Clone)]
+#[derive(Debug, Display, Clone, PartialEq)]
 pub enum ColorOption {
     Background,
     Text,
     Primary,
-    _Success,
-    _Warning,
-    _Danger
+    Success,
+    Warning,
+    Danger
 }
 
 #[derive(Debug, Clone)]
@@ -18,6 +23,34 @@ pub enum FullscreenKey {
         F11
 }
 
+#[derive(Debug, Clone)]
+pub enum FilterEdit {
+    Name(String),
+    Pattern(String),
+    Replace(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum TrainingEdit {
+    Baseline(String),
+    Ramp(String),
+    DrillInterval(String),
+    DrillLength(String),
+    DrillBoost(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum WarmupEdit {
+    Words(String),
+    StartSpeed(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
     DisplayColor(ColorOption),
@@ -34,7 +67,103 @@ pub enum Message {
     ToggleFullscreen(FullscreenKey),
     TextAnimation(Instant),
     TextSizeChanged(f32),
+    ToggleSubtitleTiming,
     FileDialogFont,
     FontChanged(Result<(), iced::font::Error>),
-    FileLoaded(Option<(String, String, usize)>),
+    FileLoaded(Option<LoadedText>),
+    ArchiveLoaded(Vec<ArchiveEntry>),
+    ArchiveEntrySelected(usize),
+    ArchiveClosed,
+    ReadClipboard,
+    ClipboardRead(Option<String>),
+    SaveToLibrary,
+    FileHovered,
+    FileHoverLeft,
+    FileDropped(std::path::PathBuf),
+    ShowLibrary,
+    ShowReader,
+    ShowStripped,
+    ShowStatistics,
+    FinishSession,
+    GoalKindChanged(GoalKind),
+    QuizAnswered(usize),
+    QuizContinue,
+    ToggleQuiz,
+    QuizIntervalChanged(String),
+    ToggleTraining,
+    EditTraining(TrainingEdit),
+    EditWarmup(WarmupEdit),
+    AutoPauseModeChanged(Boundary, PauseMode),
+    KeyPressed(Key, Modifiers, bool),
+    ShowKeys,
+    RebindKey(usize),
+    RestoreKeyBindings,
+    WordScrolled(ScrollDelta),
+    ScrollModeChanged(ScrollMode),
+    PreviousSentence,
+    NextSentence,
+    MouseMoved,
+    ControlsHovered(bool),
+    ShowSettings,
+    VelocityChanged(u32),
+    ResetFont,
+    ClearTextHistory,
+    ThemeSelected(String),
+    ThemeNameChanged(String),
+    SaveTheme,
+    DeleteTheme(String),
+    ImportTheme,
+    ExportTheme,
+    ThemeModeChanged(ThemeMode),
+    AutoLightThemeChanged(String),
+    AutoDarkThemeChanged(String),
+    LightFromChanged(DayTime),
+    DarkFromChanged(DayTime),
+    SystemThemeChanged(theme::Mode),
+    ThemeTick,
+    DismissNotice,
+    AutoPauseDurationChanged(Boundary, String),
+    GoalAmountChanged(String),
+    ToggleStripBoilerplate,
+    ToggleStripBackMatter,
+    ShowFilters,
+    EditFilter(usize, FilterEdit),
+    ToggleFilter(usize),
+    ToggleTextFilter(usize),
+    AddFilter,
+    RemoveFilter(usize),
+    RestoreFilterPresets,
+    ApplyFilters,
+    ToggleSidebar(Sidebar),
+    BookmarkLabelChanged(String),
+    AddBookmark,
+    JumpToBookmark(usize),
+    RemoveBookmark(usize),
+    JumpToChapter(usize),
+    AddHighlight,
+    ShowHighlights,
+    HighlightNoteChanged(usize, String),
+    SaveHighlights,
+    JumpToHighlight(usize),
+    RemoveHighlight(usize),
+    ExportHighlights(ExportFormat),
+    OpenSearch,
+    SearchQueryChanged(String),
+    ToggleSearchRegex,
+    ToggleSearchCase,
+    SearchNext,
+    SearchPrevious,
+    JumpToResult(usize),
+    OpenLibraryText(String),
+    IndexLoaded(LibraryIndex),
+    IndexSaved,
+    LibraryQueryChanged(String),
+    SearchLibrary,
+    LibrarySearched(String, Vec<LibrarySearchResult>),
+    OpenLibraryResult(usize),
+    SetInbox,
+    ClearInbox,
+    InboxTick,
+    InboxScanned(String, Vec<(std::path::PathBuf, FileStamp)>),
+    InboxImported(std::path::PathBuf, FileStamp, Option<Vec<String>>),
 }
@@ -0,0 +1,157 @@
+use serde::{Serialize, Deserialize};
+
+// Text ready to be read, produced by file processing
+#[derive(Debug, Clone)]
+pub struct LoadedText {
+    pub content: String,
+    pub name: String,
+    pub meta: TextMeta,
+    pub index: usize,
+    // Not backed by a file, e.g. clipboard or stdin
+    pub adhoc: bool,
+}
+
+// Metadata saved next to each cached text
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TextMeta {
+    pub title: String,
+    pub cues: Vec<Cue>,
+    pub stripped: Vec<StrippedSection>,
+    // Ids of the filter rules turned off for this text only
+    pub disabled_filters: Vec<String>,
+    pub bookmarks: Vec<Bookmark>,
+    pub highlights: Vec<Highlight>,
+    pub toc: Vec<TocEntry>,
+}
+
+// Chapter heading, line is the line of the cached text where it starts
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TocEntry {
+    pub title: String,
+    pub level: usize,
+    pub line: usize,
+}
+
+// Named word position saved by the reader
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Bookmark {
+    pub label: String,
+    pub index: usize,
+}
+
+// Sentence flagged while reading, start and end are word positions
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub note: String,
+}
+
+// Boilerplate removed from a text before it was cached
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StrippedSection {
+    pub label: String,
+    pub text: String,
+}
+
+// Subtitle cue timing, in milliseconds from the start of the file
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Cue {
+    pub start: u64,
+    pub end: u64,
+    pub words: usize,
+}
+
+// Structural break after a word, from the smallest to the largest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Boundary {
+    Paragraph,
+    Section,
+    Chapter,
+}
+
+// Panel shown next to the reader
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sidebar {
+    Bookmarks,
+    Search,
+    Contents,
+}
+
+// Readable file found inside a zip or tar archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+// Cached text listed in the library
+#[derive(Debug, Clone)]
+pub struct LibraryEntry {
+    pub name: String,
+    pub title: String,
+}
+
+// Word where a search matched, with the words around it
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub index: usize,
+    pub snippet: String,
+}
+
+// Phrase found in one of the cached texts
+#[derive(Debug, Clone)]
+pub struct LibrarySearchResult {
+    pub name: String,
+    pub title: String,
+    pub index: usize,
+    pub snippet: String,
+}
+
+// Continuous reading of one text, start and end are unix seconds
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReadingSession {
+    pub text: String,
+    pub title: String,
+    pub start: i64,
+    pub end: i64,
+    // Time spent reading, pauses excluded
+    pub reading_ms: u64,
+    pub words: usize,
+    pub pauses: usize,
+    pub rewinds: usize,
+    // Comprehension questions asked and answered right
+    #[serde(default)]
+    pub questions: usize,
+    #[serde(default)]
+    pub correct: usize,
+}
+
+// Reading done over one day or one week
+#[derive(Debug, Clone)]
+pub struct PeriodTotal {
+    pub label: String,
+    pub words: usize,
+    pub reading_ms: u64,
+}
+
+// Shown when a reading session ends
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub session: ReadingSession,
+    // Today's reading, the session included
+    pub today: PeriodTotal,
+    pub streak: usize,
+    // Training baseline before and after the session
+    pub baseline: Option<(u32, u32)>,
+}
+
+// Fill in the blank question on a recently read sentence
+#[derive(Debug, Clone)]
+pub struct Quiz {
+    pub sentence: String,
+    pub choices: Vec<String>,
+    pub answer: usize,
+}
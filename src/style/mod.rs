@@ -1,5 +1,10 @@
 pub mod theme;
 
 pub use theme::custom_theme_from_state;
+pub use theme::theme_presets;
 pub use theme::hex_to_rgb;
 pub use theme::rgb_to_hex;
+pub use theme::contrast_ratio;
+pub use theme::nearest_contrast;
+pub use theme::AA_CONTRAST;
+pub use theme::AAA_CONTRAST;
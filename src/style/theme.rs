@@ -1,7 +1,7 @@
 use iced::Theme;
 use iced::theme::Palette;
 use iced::Color;
-use crate::infrastructure::ColorTheme;
+use crate::infrastructure::{ColorTheme, NamedTheme};
 
 pub fn custom_theme_from_state(current_theme: &ColorTheme) -> Theme {
     let custom_palette: Palette = Palette {
@@ -15,6 +15,68 @@ pub fn custom_theme_from_state(current_theme: &ColorTheme) -> Theme {
     Theme::custom("custom", custom_palette)
 }
 
+// Built-in palettes offered next to the saved themes
+pub fn theme_presets() -> Vec<NamedTheme> {
+    [
+        ("Light", ["#FFFEF9", "#15161B", "#C17F5A", "#809C6C", "#AE363F", "#DF3535"]),
+        ("Dark", ["#1E1F24", "#E6E4DF", "#D9955F", "#8FB573", "#E0B04A", "#E5534B"]),
+        ("Sepia", ["#F4ECD8", "#5B4636", "#A0522D", "#6B8E23", "#B8860B", "#B22222"]),
+        ("High contrast", ["#000000", "#FFFFFF", "#FFFF00", "#00FF00", "#FFA500", "#FF4040"]),
+        ("Solarized light", ["#FDF6E3", "#586E75", "#268BD2", "#859900", "#B58900", "#DC322F"]),
+        ("Solarized dark", ["#002B36", "#93A1A1", "#268BD2", "#859900", "#B58900", "#DC322F"]),
+    ]
+    .into_iter()
+    .map(|(name, [background, text, primary, success, warning, danger])| NamedTheme {
+        name: name.to_string(),
+        colors: ColorTheme {
+            background: background.to_string(),
+            text: text.to_string(),
+            primary: primary.to_string(),
+            success: success.to_string(),
+            warning: warning.to_string(),
+            danger: danger.to_string(),
+        },
+    })
+    .collect()
+}
+
+// WCAG contrast ratios for normal text
+pub const AA_CONTRAST: f32 = 4.5;
+pub const AAA_CONTRAST: f32 = 7.0;
+
+fn relative_luminance(c: Color) -> f32 {
+    let linear = |v: f32| if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) };
+    0.2126 * linear(c.r) + 0.7152 * linear(c.g) + 0.0722 * linear(c.b)
+}
+
+pub fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+// The color mixed with as little white or black as possible to reach the ratio
+// against the background, and whether it got there. When neither does,
+// plain white or black is the best available
+pub fn nearest_contrast(color: Color, background: Color, ratio: f32) -> (Color, bool) {
+    let mix = |target: f32, t: f32| {
+        let channel = |v: f32| v + (target - v) * t;
+        // Rounded like the saved hex so the suggestion keeps its ratio
+        hex_to_rgb(&rgb_to_hex(Color::from_rgb(channel(color.r), channel(color.g), channel(color.b))))
+    };
+    let reach = |target: f32| (0..=100)
+    .map(|step| step as f32 / 100.0)
+    .find(|t| contrast_ratio(mix(target, *t), background) >= ratio)
+    .map(|t| (t, mix(target, t)));
+
+    match (reach(1.0), reach(0.0)) {
+        (Some(lighter), Some(darker)) => (if lighter.0 <= darker.0 { lighter.1 } else { darker.1 }, true),
+        (Some(lighter), None) => (lighter.1, true),
+        (None, Some(darker)) => (darker.1, true),
+        (None, None) if contrast_ratio(Color::WHITE, background) >= contrast_ratio(Color::BLACK, background) => (Color::WHITE, false),
+        (None, None) => (Color::BLACK, false),
+    }
+}
+
 pub fn hex_to_rgb(s: &str) -> Color {
     if !s.starts_with("#") || s.len() != 7 { panic!("Invalid hex color format") };
 
@@ -26,3 +88,29 @@ pub fn hex_to_rgb(s: &str) -> Color {
 pub fn rgb_to_hex(c: Color) -> String {
     format!("#{:02X}{:02X}{:02X}",(c.r * 255.0) as u8, (c.g * 255.0) as u8, (c.b * 255.0) as u8)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contrast_ratio_spans_one_to_twenty_one() {
+        assert!((contrast_ratio(Color::BLACK, Color::WHITE) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(Color::WHITE, Color::WHITE) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn nearest_contrast_reaches_the_target() {
+        let (color, reached) = nearest_contrast(hex_to_rgb("#999999"), Color::WHITE, AA_CONTRAST);
+        assert!(reached);
+        assert!(contrast_ratio(color, Color::WHITE) >= AA_CONTRAST);
+    }
+
+    #[test]
+    fn nearest_contrast_reports_an_unreachable_target() {
+        // Nothing reaches 7:1 against mid grey
+        let (color, reached) = nearest_contrast(Color::WHITE, hex_to_rgb("#777777"), AAA_CONTRAST);
+        assert!(!reached);
+        assert_eq!(color, Color::BLACK);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod ring;
 pub mod views;
 
 pub use views::main_view;
@@ -0,0 +1,35 @@
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke, path::Arc};
+use iced::{mouse, Color, Radians, Rectangle, Renderer, Theme};
+
+// Circular progress indicator, filled clockwise from the top
+pub struct ProgressRing {
+    pub progress: f32,
+    pub width: f32,
+}
+
+impl<Message> canvas::Program<Message> for ProgressRing {
+    type State = ();
+
+    fn draw(&self, _state: &(), renderer: &Renderer, theme: &Theme, bounds: Rectangle, _cursor: mouse::Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.palette();
+        let center = frame.center();
+        let radius = (bounds.width.min(bounds.height) - self.width) / 2.0;
+
+        let track = Color { a: 0.2, ..palette.text };
+        frame.stroke(&Path::circle(center, radius), Stroke::default().with_width(self.width).with_color(track));
+
+        if self.progress > 0.0 {
+            let start = -std::f32::consts::FRAC_PI_2;
+            let arc = Path::new(|builder| builder.arc(Arc {
+                center,
+                radius,
+                start_angle: Radians(start),
+                end_angle: Radians(start + self.progress * std::f32::consts::TAU),
+            }));
+            let color = if self.progress >= 1.0 { palette.success } else { palette.primary };
+            frame.stroke(&arc, Stroke::default().with_width(self.width).with_color(color));
+        }
+        vec![frame.into_geometry()]
+    }
+}
@@ -1,4 +1,5 @@
-use iced::widget::{button, container, column, text, rich_text, span, row, Space, slider};
+use iced::widget::{button, container, column, text, rich_text, span, row, Space, slider, scrollable, checkbox, text_input, progress_bar, pick_list, tooltip, canvas, mouse_area};
+use iced::mouse;
 use iced_aw::helpers::color_picker;
 use iced_aw::Spinner;
 use iced::Element;
@@ -7,13 +8,19 @@ use iced::time;
 use iced::Color;
 use iced::never;
 use iced::Fill;
+use iced::FillPortion;
 use iced::Center;
-use crate::app::State;
-use crate::message::{Message, ColorOption};
-use crate::style::hex_to_rgb;
+use iced::{Theme, Border, Padding};
+use crate::app::{State, Screen, SEARCH_INPUT};
+use crate::message::{Message, FullscreenKey, ColorOption, FilterEdit, ExportFormat, TrainingEdit, WarmupEdit};
+use crate::infrastructure::{GoalKind, PauseMode, ScrollMode, ThemeMode, DayTime, is_valid_filter, key_conflict, daily_totals, weekly_totals, reading_streaks, session_wpm};
+use crate::model::{ArchiveEntry, ReadingSession, PeriodTotal, SessionSummary, Quiz, Boundary, Sidebar};
+use crate::view::ring::ProgressRing;
+use chrono::TimeZone;
+use crate::style::{hex_to_rgb, rgb_to_hex};
 
 pub fn main_view(current_state: &State) -> Element<'_, Message> {
-    if current_state.text_loader {
+    let view: Element<'_, Message> = if current_state.text_loader {
         container(
             Spinner::new()
             .width(100)
@@ -23,85 +30,1024 @@ pub fn main_view(current_state: &State) -> Element<'_, Message> {
         .center_x(Fill)
         .center_y(Fill)
         .into()
+    } else if let Some(entries) = &current_state.archive {
+        archive_picker_view(entries)
+    } else if current_state.screen == Screen::Library {
+        library_view(current_state)
+    } else if current_state.screen == Screen::Stripped {
+        stripped_view(current_state)
+    } else if current_state.screen == Screen::Filters {
+        filters_view(current_state)
+    } else if current_state.screen == Screen::Highlights {
+        highlights_view(current_state)
+    } else if current_state.screen == Screen::Statistics {
+        statistics_view(current_state)
+    } else if current_state.screen == Screen::Keys {
+        keys_view(current_state)
+    } else if current_state.screen == Screen::Settings {
+        settings_view(current_state)
+    } else if let (Screen::Summary, Some(summary)) = (current_state.screen, &current_state.summary) {
+        summary_view(current_state, summary)
+    } else if let (Screen::Quiz, Some(quiz)) = (current_state.screen, &current_state.quiz) {
+        quiz_view(quiz, current_state.quiz_choice)
     } else if current_state.fullscreen {
         let word: [&str; 3]= current_state.get_word();
-        container(
+        let view = container(
             column![
                 row![
                     text(current_state.get_title()).size(10),
                     Space::new()
                     .width(Fill),
+                    training_status(current_state),
+                    goal_ring(current_state, 20.0),
                 ]
+                .spacing(10)
                 .height(Fill),
-                rich_text![
-                    span(word[0])
-                    .color(text_animation(&current_state.get_state_theme().text, &current_state.text_animation, current_state.text_instant))
-                    .font(current_state.current_font),
-                    span(word[1])
-                    .color(text_animation(&current_state.get_state_theme().primary, &current_state.text_animation, current_state.text_instant))
-                    .font(current_state.current_font),
-                    span(word[2])
-                    .color(text_animation(&current_state.get_state_theme().text, &current_state.text_animation, current_state.text_instant))
-                    .font(current_state.current_font),
-                ].on_link_click(never)
-                .size(current_state.get_text_size()),
-                Space::new()
+                mouse_area(
+                    rich_text![
+                        span(word[0])
+                        .color(text_animation(&current_state.get_state_theme().text, &current_state.text_animation, current_state.text_instant))
+                        .font(current_state.current_font),
+                        span(word[1])
+                        .color(text_animation(&current_state.get_state_theme().primary, &current_state.text_animation, current_state.text_instant))
+                        .font(current_state.current_font),
+                        span(word[2])
+                        .color(text_animation(&current_state.get_state_theme().text, &current_state.text_animation, current_state.text_instant))
+                        .font(current_state.current_font),
+                    ].on_link_click(never)
+                    .size(current_state.get_text_size())
+                )
+                .on_press(Message::SpacePressed)
+                .on_scroll(Message::WordScrolled)
+                .interaction(mouse::Interaction::Pointer),
+                column![
+                    Space::new()
+                    .height(Fill),
+                    current_state.controls_visible().then(|| mini_controls(current_state)),
+                ]
                 .height(Fill),
             ]
             .align_x(Center)
             .spacing(10)
         )
         .center_x(Fill)
-        .center_y(Fill)
+        .center_y(Fill);
+        mouse_area(view)
+        .on_move(|_| Message::MouseMoved)
         .into()
     } else {
         let word: [&str; 3]= current_state.get_word();
-        container(
+        container(row![
             column![
                 row![
                     text(current_state.get_title()).size(10),
                     Space::new()
                     .width(Fill),
+                    training_status(current_state),
+                    goal_ring(current_state, 20.0),
                 ]
+                .spacing(10)
                 .height(Fill),
-                rich_text![
-                    span(word[0])
-                    .color(text_animation(&current_state.get_state_theme().text, &current_state.text_animation, current_state.text_instant))
-                    .font(current_state.current_font),
-                    span(word[1])
-                    .color(text_animation(&current_state.get_state_theme().primary, &current_state.text_animation, current_state.text_instant))
-                    .font(current_state.current_font),
-                    span(word[2])
-                    .color(text_animation(&current_state.get_state_theme().text, &current_state.text_animation, current_state.text_instant))
-                    .font(current_state.current_font),
-                ].on_link_click(never)
-                .size(current_state.get_text_size()),
+                mouse_area(
+                    rich_text![
+                        span(word[0])
+                        .color(text_animation(&current_state.get_state_theme().text, &current_state.text_animation, current_state.text_instant))
+                        .font(current_state.current_font),
+                        span(word[1])
+                        .color(text_animation(&current_state.get_state_theme().primary, &current_state.text_animation, current_state.text_instant))
+                        .font(current_state.current_font),
+                        span(word[2])
+                        .color(text_animation(&current_state.get_state_theme().text, &current_state.text_animation, current_state.text_instant))
+                        .font(current_state.current_font),
+                    ].on_link_click(never)
+                    .size(current_state.get_text_size())
+                )
+                .on_press(Message::SpacePressed)
+                .on_scroll(Message::WordScrolled)
+                .interaction(mouse::Interaction::Pointer),
                 Space::new()
                 .height(Fill),
                 row![
                     Space::new()
                     .width(Fill),
+                    // Playback only, everything else is reached from the settings screen
                     button("Reset position").on_press(Message::ResetIndex),
-                    button("Upload file").on_press(Message::FileDialog),
-                    button("Upload font").on_press(Message::FileDialogFont),
-                    slider(10.0..=250.0, current_state.get_text_size(), Message::TextSizeChanged),
-                    color_pick_bg_view(current_state.display.background, &current_state.get_state_theme().background, ColorOption::Background),
-                    color_pick_bg_view(current_state.display.text, &current_state.get_state_theme().text, ColorOption::Text),
-                    color_pick_bg_view(current_state.display.primary, &current_state.get_state_theme().primary, ColorOption::Primary)    
+                    button("Previous sentence").on_press(Message::PreviousSentence),
+                    button(if current_state.is_paused() { "Resume" } else { "Pause" }).on_press(Message::SpacePressed),
+                    button("Next sentence").on_press(Message::NextSentence),
+                    current_state.text_unsaved.then(|| button("Save to library").on_press(Message::SaveToLibrary)),
+                    current_state.has_session().then(|| button("Finish session").on_press(Message::FinishSession)),
+                    button("Settings").on_press(Message::ShowSettings),
                 ]
                 .align_y(Center)
                 .spacing(10)
                 .padding(10)
             ]
             .align_x(Center)
-            .spacing(10)
-        )
+            .spacing(10),
+            current_state.sidebar.map(|sidebar| sidebar_view(current_state, sidebar)),
+        ])
         .center_x(Fill)
         .center_y(Fill)
         .into()
+    };
+
+    let view = match &current_state.notice {
+        Some(notice) => column![
+            row![
+                text(notice).style(text::danger),
+                button("Dismiss").on_press(Message::DismissNotice),
+            ]
+            .align_y(Center)
+            .spacing(10)
+            .padding(5),
+            view,
+        ]
+        .align_x(Center)
+        .into(),
+        None => view,
+    };
+
+    if current_state.file_hover {
+        // Drop zone highlight while a file is dragged over the window
+        container(view)
+        .style(|theme: &Theme| container::Style {
+            border: Border {
+                color: theme.palette().primary,
+                width: 4.0,
+                radius: 0.0.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+    } else {
+        view
+    }
+}
+
+fn library_view(current_state: &State) -> Element<'_, Message> {
+    let texts = current_state.library.iter()
+    .fold(column![].spacing(5), |texts, entry| {
+        texts.push(button(text(&entry.title)).on_press(Message::OpenLibraryText(entry.name.clone())))
+    });
+
+    let results = current_state.library_results.iter().enumerate()
+    .fold(column![].spacing(5), |results, (i, result)| {
+        results.push(button(column![
+            text(&result.title).size(12),
+            text(&result.snippet),
+        ])
+        .style(button::secondary)
+        .on_press(Message::OpenLibraryResult(i))
+        .width(Fill))
+    });
+
+    container(
+        column![
+            text(if current_state.library.is_empty() { "The library is empty" } else { "Library" }),
+            row![
+                text_input("Search all texts", &current_state.library_query)
+                .on_input(Message::LibraryQueryChanged)
+                .on_submit(Message::SearchLibrary),
+                button("Search").on_press(Message::SearchLibrary),
+            ]
+            .spacing(10),
+            if current_state.library_results.is_empty() {
+                scrollable(texts).height(Fill)
+            } else {
+                scrollable(results).height(Fill)
+            },
+            button("Back").on_press(Message::ShowReader),
+        ]
+        .align_x(Center)
+        .spacing(10)
+        .padding(10)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
+fn sidebar_view(current_state: &State, sidebar: Sidebar) -> Element<'_, Message> {
+    let content = match sidebar {
+        Sidebar::Bookmarks => bookmarks_view(current_state),
+        Sidebar::Search => search_view(current_state),
+        Sidebar::Contents => contents_view(current_state),
+    };
+    container(content)
+    .width(250)
+    .height(Fill)
+    .padding(10)
+    .into()
+}
+
+fn bookmarks_view(current_state: &State) -> Element<'_, Message> {
+    let bookmarks = current_state.text_meta.bookmarks.iter().enumerate()
+    .fold(column![].spacing(5), |bookmarks, (i, bookmark)| {
+        bookmarks.push(row![
+            button(text(format!("{} ({})", bookmark.label, bookmark.index + 1)))
+            .on_press(Message::JumpToBookmark(i))
+            .width(Fill),
+            button("x").on_press(Message::RemoveBookmark(i)),
+        ]
+        .spacing(5))
+    });
+
+    column![
+        text("Bookmarks"),
+        row![
+            text_input("Label (optional)", &current_state.bookmark_label)
+            .on_input(Message::BookmarkLabelChanged)
+            .on_submit(Message::AddBookmark),
+            button("Add").on_press(Message::AddBookmark),
+        ]
+        .spacing(5),
+        scrollable(bookmarks).height(Fill),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn contents_view(current_state: &State) -> Element<'_, Message> {
+    let current = current_state.get_current_chapter();
+    let entries = current_state.get_contents().into_iter().enumerate()
+    .fold(column![].spacing(5), |entries, (i, (entry, progress))| {
+        let label = text(&entry.title).size(12);
+        let entry_button = if current == Some(i) {
+            button(label).style(button::primary)
+        } else {
+            button(label).style(button::secondary)
+        };
+        entries.push(column![
+            entry_button.on_press(Message::JumpToChapter(i)).width(Fill),
+            progress_bar(0.0..=1.0, progress).girth(3),
+        ]
+        .padding(Padding::ZERO.left((entry.level.saturating_sub(1) * 12) as f32)))
+    });
+
+    let content: Element<'_, Message> = if current_state.text_meta.toc.is_empty() {
+        text("No chapters found in this text").size(12).into()
+    } else {
+        scrollable(entries).height(Fill).into()
+    };
+
+    column![
+        text("Contents"),
+        content,
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn search_view(current_state: &State) -> Element<'_, Message> {
+    let search = &current_state.search;
+    let results = search.results.iter().enumerate()
+    .fold(column![].spacing(5), |results, (i, result)| {
+        let label = text(&result.snippet).size(12);
+        let result_button = if search.selected == Some(i) {
+            button(label).style(button::primary)
+        } else {
+            button(label).style(button::secondary)
+        };
+        results.push(result_button.on_press(Message::JumpToResult(i)).width(Fill))
+    });
+
+    let status = match &search.error {
+        Some(e) => e.lines().last().unwrap_or_default().to_string(),
+        None if search.query.is_empty() => String::new(),
+        None => match search.selected {
+            Some(i) => format!("{} of {}", i + 1, search.results.len()),
+            None => format!("{} results", search.results.len()),
+        },
+    };
+
+    column![
+        text_input("Search", &search.query)
+        .id(SEARCH_INPUT)
+        .on_input(Message::SearchQueryChanged)
+        .on_submit(Message::SearchNext),
+        row![
+            checkbox(search.case_insensitive)
+            .label("Ignore case")
+            .on_toggle(|_| Message::ToggleSearchCase),
+            checkbox(search.regex)
+            .label("Regex")
+            .on_toggle(|_| Message::ToggleSearchRegex),
+        ]
+        .spacing(10),
+        row![
+            text(status).size(12).width(Fill),
+            button("<").on_press(Message::SearchPrevious),
+            button(">").on_press(Message::SearchNext),
+        ]
+        .align_y(Center)
+        .spacing(5),
+        scrollable(results).height(Fill),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn highlights_view(current_state: &State) -> Element<'_, Message> {
+    let highlights = current_state.text_meta.highlights.iter().enumerate()
+    .fold(column![].spacing(15), |highlights, (i, highlight)| {
+        highlights.push(column![
+            text(&highlight.text),
+            row![
+                text(format!("Words {}-{}", highlight.start + 1, highlight.end)).size(12),
+                text_input("Note", &highlight.note)
+                .on_input(move |note| Message::HighlightNoteChanged(i, note))
+                .on_submit(Message::SaveHighlights),
+                button("Go to").on_press(Message::JumpToHighlight(i)),
+                button("Remove").on_press(Message::RemoveHighlight(i)),
+            ]
+            .align_y(Center)
+            .spacing(10),
+        ]
+        .spacing(5))
+    });
+
+    container(
+        column![
+            text(if current_state.text_meta.highlights.is_empty() {
+                "No highlights yet, press H while reading to highlight the current sentence".to_string()
+            } else {
+                format!("Highlights of {}", current_state.get_title())
+            }),
+            scrollable(highlights).height(Fill),
+            row![
+                button("Export Markdown").on_press(Message::ExportHighlights(ExportFormat::Markdown)),
+                button("Export JSON").on_press(Message::ExportHighlights(ExportFormat::Json)),
+                button("Done").on_press(Message::SaveHighlights),
+            ]
+            .spacing(10),
+        ]
+        .align_x(Center)
+        .spacing(10)
+        .padding(10)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
+fn keys_view(current_state: &State) -> Element<'_, Message> {
+    let bindings = current_state.get_key_bindings();
+    let list = bindings.iter().enumerate()
+    .fold(column![].spacing(5), |list, (i, binding)| {
+        let label = if current_state.rebinding == Some(i) { "Press a key...".to_string() } else { binding.key.clone() };
+        // Bindings saved before a conflict check could still share a key
+        let conflict = key_conflict(bindings, i, &binding.key).map(|action| {
+            text(format!("Also used by {}", action)).size(12).style(text::danger)
+        });
+        list.push(row![
+            text(binding.action.to_string()).width(200),
+            button(text(label)).on_press(Message::RebindKey(i)).width(150),
+            conflict,
+        ]
+        .align_y(Center)
+        .spacing(10))
+    });
+
+    container(
+        column![
+            text("Click a key to change it, Escape cancels"),
+            current_state.key_notice.as_ref().map(|notice| text(notice).style(text::danger)),
+            scrollable(list).height(Fill),
+            row![
+                button("Restore defaults").on_press(Message::RestoreKeyBindings),
+                button("Back").on_press(Message::ShowSettings),
+            ]
+            .spacing(10),
+        ]
+        .align_x(Center)
+        .spacing(10)
+        .padding(10)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
+fn statistics_view(current_state: &State) -> Element<'_, Message> {
+    let sessions = &current_state.sessions;
+    let days = daily_totals(sessions, 14);
+    let weeks = weekly_totals(sessions, 8);
+    let (streak, longest) = reading_streaks(sessions);
+    let (questions, correct) = sessions.iter().fold((0, 0), |(q, c), s| (q + s.questions, c + s.correct));
+    let minutes = |total: &PeriodTotal| total.reading_ms / 60_000;
+    let today = days.last().map(|t| format!("Today: {} words, {} min", t.words, minutes(t))).unwrap_or_default();
+    let week = weeks.last().map(|t| format!("This week: {} words, {} min", t.words, minutes(t))).unwrap_or_default();
+
+    let recent: Vec<&ReadingSession> = sessions.iter().rev().filter(|s| s.words > 0).take(30).collect();
+    let speeds: Vec<(String, f32)> = recent.iter().rev()
+    .map(|s| (format_time(s.start, "%d/%m"), session_wpm(s)))
+    .collect();
+    let history = recent.iter().take(10)
+    .fold(column![].spacing(5), |history, session| {
+        history.push(row![
+            text(&session.title).width(Fill),
+            text(format_time(session.start, "%d/%m %H:%M")).width(90),
+            text(format!("{} words", session.words)).width(90),
+            text(format!("{:.0} wpm", session_wpm(session))).width(70),
+            text(format!("{} pauses", session.pauses)).width(80),
+            text(format!("{} rewinds", session.rewinds)).width(80),
+            text(if session.questions > 0 { format!("{}/{} correct", session.correct, session.questions) } else { String::new() }).width(80),
+        ]
+        .spacing(10))
+    });
+
+    container(
+        column![
+            row![
+                text(today),
+                text(week),
+                text(format!("Streak: {} days (longest {})", streak, longest)),
+                (questions > 0).then(|| text(format!("Comprehension: {}% of {} questions", correct * 100 / questions, questions))),
+                goal_ring(current_state, 30.0),
+            ]
+            .align_y(Center)
+            .spacing(30),
+            text("Words per day"),
+            bar_chart(days.iter().map(|t| (t.label.clone(), t.words as f32)).collect()),
+            text("Words per week"),
+            bar_chart(weeks.iter().map(|t| (t.label.clone(), t.words as f32)).collect()),
+            text("Speed per session (wpm)"),
+            bar_chart(speeds),
+            text(if recent.is_empty() { "No reading sessions yet" } else { "Recent sessions" }),
+            scrollable(history).height(Fill),
+            button("Back").on_press(Message::ShowReader),
+        ]
+        .align_x(Center)
+        .spacing(10)
+        .padding(10)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
+fn summary_view<'a>(current_state: &'a State, summary: &'a SessionSummary) -> Element<'a, Message> {
+    let session = &summary.session;
+    let goal = current_state.get_goal();
+    let goal_status = goal.progress(summary.today.words, summary.today.reading_ms).map(|progress| {
+        if progress >= 1.0 {
+            "Today's goal is met".to_string()
+        } else {
+            format!("Today's goal: {}", goal_label(current_state, summary.today.words, summary.today.reading_ms))
+        }
+    });
+
+    container(
+        column![
+            text(&session.title).size(20),
+            text(format!("{} words in {} min, {:.0} wpm", session.words, session.reading_ms / 60_000, session_wpm(session))),
+            text(format!("{} pauses, {} rewinds", session.pauses, session.rewinds)),
+            (session.questions > 0).then(|| text(format!("{} of {} questions answered right", session.correct, session.questions))),
+            summary.baseline.map(|(before, after)| text(format!("Training baseline: {} wpm, next session {} wpm", before, after))),
+            goal_status.map(text),
+            text(format!("Streak: {} days", summary.streak)),
+            row![
+                button("Continue").on_press(Message::ShowReader),
+                button("Statistics").on_press(Message::ShowStatistics),
+            ]
+            .spacing(10),
+        ]
+        .align_x(Center)
+        .spacing(10)
+        .padding(10)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
+fn quiz_view(quiz: &Quiz, choice: Option<usize>) -> Element<'_, Message> {
+    let choices = quiz.choices.iter().enumerate()
+    .fold(row![].spacing(10), |choices, (i, word)| {
+        let style = match choice {
+            Some(_) if i == quiz.answer => button::success,
+            Some(chosen) if i == chosen => button::danger,
+            _ => button::secondary,
+        };
+        let choice_button = button(text(word)).style(style);
+        choices.push(if choice.is_none() { choice_button.on_press(Message::QuizAnswered(i)) } else { choice_button })
+    });
+    let result = choice.map(|chosen| if chosen == quiz.answer { "Right!" } else { "Not quite" });
+
+    container(
+        column![
+            text("Which word fills the blank?"),
+            text(&quiz.sentence).size(20),
+            choices,
+            result.map(text),
+            button(if choice.is_some() { "Continue" } else { "Skip" }).on_press(Message::QuizContinue),
+        ]
+        .align_x(Center)
+        .spacing(15)
+        .padding(10)
+        .max_width(700)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
+fn settings_view(current_state: &State) -> Element<'_, Message> {
+    let theme = current_state.get_manual_theme();
+    let display = &current_state.display;
+    let goal = current_state.get_goal();
+    let goal_amount = if goal.amount == 0 { String::new() } else { goal.amount.to_string() };
+    let quiz = current_state.get_quiz_settings();
+    let quiz_interval = if quiz.interval == 0 { String::new() } else { quiz.interval.to_string() };
+    let velocity = current_state.get_velocity();
+
+    let reading = column![
+        row![
+            text(format!("Word delay {} ms ({} wpm)", velocity, 60_000 / velocity.max(1))).width(200),
+            slider(200..=2000, velocity, Message::VelocityChanged).step(10u32),
+        ]
+        .align_y(Center)
+        .spacing(10),
+        checkbox(current_state.get_subtitle_timing())
+        .label("Follow subtitle timing for subtitle files")
+        .on_toggle(|_| Message::ToggleSubtitleTiming),
+        row![
+            text("Daily goal"),
+            pick_list([GoalKind::Minutes, GoalKind::Words], Some(goal.kind), Message::GoalKindChanged),
+            text_input("None", &goal_amount)
+            .on_input(Message::GoalAmountChanged)
+            .width(80),
+        ]
+        .align_y(Center)
+        .spacing(10),
+        row![
+            checkbox(quiz.enabled)
+            .label("Comprehension questions every")
+            .on_toggle(|_| Message::ToggleQuiz),
+            text_input("0", &quiz_interval)
+            .on_input(Message::QuizIntervalChanged)
+            .width(80),
+            text("words and at chapter ends"),
+        ]
+        .align_y(Center)
+        .spacing(10),
+        training_settings_view(current_state),
+        warmup_settings_view(current_state),
+        text("Auto-pause"),
+        auto_pause_view(current_state),
+    ]
+    .spacing(10);
+
+    let display_settings = row![
+        text(format!("Text size {:.0}", current_state.get_text_size())).width(200),
+        slider(10.0..=250.0, current_state.get_text_size(), Message::TextSizeChanged),
+    ]
+    .align_y(Center)
+    .spacing(10);
+
+    let current_theme = current_state.get_current_theme_name();
+    let theme_settings = column![
+        row![
+            pick_list(current_state.get_theme_names(), current_theme.clone(), Message::ThemeSelected)
+            .placeholder("Custom"),
+            current_theme.filter(|name| current_state.is_saved_theme(name)).map(|name| {
+                button("Delete theme").on_press(Message::DeleteTheme(name))
+            }),
+            text_input("Theme name", &current_state.theme_name)
+            .on_input(Message::ThemeNameChanged)
+            .on_submit(Message::SaveTheme)
+            .width(200),
+            button("Save theme").on_press(Message::SaveTheme),
+            button("Import").on_press(Message::ImportTheme),
+            button("Export").on_press(Message::ExportTheme),
+        ]
+        .align_y(Center)
+        .spacing(10),
+        current_state.theme_notice.as_ref().map(text),
+        auto_theme_view(current_state),
+        row![
+            color_pick_bg_view(display.background, &theme.background, ColorOption::Background),
+            color_pick_bg_view(display.text, &theme.text, ColorOption::Text),
+            color_pick_bg_view(display.primary, &theme.primary, ColorOption::Primary),
+        ]
+        .spacing(10),
+        row![
+            color_pick_bg_view(display.success, &theme.success, ColorOption::Success),
+            color_pick_bg_view(display.warning, &theme.warning, ColorOption::Warning),
+            color_pick_bg_view(display.danger, &theme.danger, ColorOption::Danger),
+        ]
+        .spacing(10),
+        contrast_view(current_state),
+    ]
+    .spacing(10);
+
+    let font = current_state.get_font_name();
+    let fonts = row![
+        text(if font == "default" { "Default font".to_string() } else { format!("Font: {}", font) }),
+        button("Upload font").on_press(Message::FileDialogFont),
+        (font != "default").then(|| button("Use default font").on_press(Message::ResetFont)),
+    ]
+    .align_y(Center)
+    .spacing(10);
+
+    let keys = column![
+        row![
+            text(format!("{} key bindings", current_state.get_key_bindings().len())),
+            button("Edit key bindings").on_press(Message::ShowKeys),
+        ]
+        .align_y(Center)
+        .spacing(10),
+        row![
+            text("Scroll wheel over the word"),
+            pick_list([ScrollMode::Words, ScrollMode::Speed], Some(current_state.get_scroll_mode()), Message::ScrollModeChanged),
+            text("Click to pause, swipe sideways to move by sentence"),
+        ]
+        .align_y(Center)
+        .spacing(10),
+    ]
+    .spacing(10);
+
+    let inbox = match current_state.get_inbox() {
+        Some(folder) => row![
+            text(format!("Watching {}", folder)),
+            button("Change inbox").on_press(Message::SetInbox),
+            button("Stop watching").on_press(Message::ClearInbox),
+        ],
+        None => row![
+            text("No inbox folder"),
+            button("Set inbox").on_press(Message::SetInbox),
+        ],
+    };
+    let storage = column![
+        inbox
+        .align_y(Center)
+        .spacing(10),
+        checkbox(current_state.get_strip_boilerplate())
+        .label("Strip Gutenberg and front matter from new texts")
+        .on_toggle(|_| Message::ToggleStripBoilerplate),
+        checkbox(current_state.get_strip_back_matter())
+        .label("Strip back matter")
+        .on_toggle(|_| Message::ToggleStripBackMatter),
+        row![
+            text(format!("{} filter rules", current_state.get_filters().len())),
+            button("Edit filters").on_press(Message::ShowFilters),
+        ]
+        .align_y(Center)
+        .spacing(10),
+        row![
+            text(format!("Reading positions saved for {} texts", current_state.get_history_len())),
+            button("Forget positions").on_press(Message::ClearTextHistory),
+        ]
+        .align_y(Center)
+        .spacing(10),
+    ]
+    .spacing(10);
+
+    let navigation = column![
+        row![
+            button("Library").on_press(Message::ShowLibrary),
+            button("Upload file").on_press(Message::FileDialog),
+            button("Read clipboard").on_press(Message::ReadClipboard),
+            button("Statistics").on_press(Message::ShowStatistics),
+            button("Highlights").on_press(Message::ShowHighlights),
+        ]
+        .spacing(10),
+        row![
+            button("Bookmarks").on_press(Message::ToggleSidebar(Sidebar::Bookmarks)),
+            button("Search").on_press(Message::ToggleSidebar(Sidebar::Search)),
+            button("Contents").on_press(Message::ToggleSidebar(Sidebar::Contents)),
+            (!current_state.text_meta.stripped.is_empty()).then(|| {
+                button(text(format!("Stripped ({})", current_state.text_meta.stripped.len()))).on_press(Message::ShowStripped)
+            }),
+        ]
+        .spacing(10),
+    ]
+    .spacing(10);
+
+    let sections = column![
+        settings_section("Go to", navigation),
+        settings_section("Reading", reading),
+        settings_section("Display", display_settings),
+        settings_section("Theme", theme_settings),
+        settings_section("Fonts", fonts),
+        settings_section("Keys and mouse", keys),
+        settings_section("Storage", storage),
+    ]
+    .spacing(25)
+    .padding(10);
+
+    container(
+        column![
+            scrollable(sections).height(Fill),
+            button("Back").on_press(Message::ShowReader),
+        ]
+        .align_x(Center)
+        .spacing(10)
+        .padding(10)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
+fn auto_theme_view(current_state: &State) -> Element<'_, Message> {
+    let auto = current_state.get_auto_theme();
+    let names = current_state.get_theme_names();
+    let times: Vec<DayTime> = (0..48).map(|i| DayTime(i * 30)).collect();
+    row![
+        text("Theme"),
+        pick_list([ThemeMode::Manual, ThemeMode::System, ThemeMode::Schedule], Some(auto.mode), Message::ThemeModeChanged),
+        (auto.mode != ThemeMode::Manual).then(|| row![
+            text("light"),
+            pick_list(names.clone(), Some(auto.light.clone()), Message::AutoLightThemeChanged),
+            (auto.mode == ThemeMode::Schedule).then(|| row![
+                text("from"),
+                pick_list(times.clone(), Some(auto.light_from), Message::LightFromChanged),
+            ]
+            .align_y(Center)
+            .spacing(5)),
+            text("dark"),
+            pick_list(names, Some(auto.dark.clone()), Message::AutoDarkThemeChanged),
+            (auto.mode == ThemeMode::Schedule).then(|| row![
+                text("from"),
+                pick_list(times, Some(auto.dark_from), Message::DarkFromChanged),
+            ]
+            .align_y(Center)
+            .spacing(5)),
+        ]
+        .align_y(Center)
+        .spacing(5)),
+    ]
+    .align_y(Center)
+    .spacing(10)
+    .into()
+}
+
+// Contrast of the palette after the last submitted color, with a fix for each one below WCAG levels
+fn contrast_view(current_state: &State) -> Element<'_, Message> {
+    current_state.contrast_warnings.iter()
+    .fold(column![].spacing(5), |warnings, warning| {
+        let suggestion = rgb_to_hex(warning.suggestion);
+        warnings.push(row![
+            text(format!("{} contrast is {:.1}:1, below WCAG {} ({}:1)", warning.option, warning.ratio, warning.level, warning.target))
+            .style(if warning.level == "AA" { text::danger } else { text::warning }),
+            button(text(if warning.reached { format!("Use {}", suggestion) } else { format!("Use {} (best available)", suggestion) }))
+            .on_press(Message::SubmitColor(warning.suggestion, warning.option.clone())),
+        ]
+        .align_y(Center)
+        .spacing(10))
+    })
+    .into()
+}
+
+fn settings_section<'a>(title: &'a str, content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    column![
+        text(title).size(20),
+        content.into(),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn training_settings_view(current_state: &State) -> Element<'_, Message> {
+    let training = current_state.get_training();
+    let field = |value: u32, on_input: fn(String) -> TrainingEdit| {
+        text_input("0", &if value == 0 { String::new() } else { value.to_string() })
+        .on_input(move |value| Message::EditTraining(on_input(value)))
+        .width(60)
+    };
+    row![
+        checkbox(training.enabled)
+        .label("Speed training from")
+        .on_toggle(|_| Message::ToggleTraining),
+        field(training.baseline, TrainingEdit::Baseline),
+        text("wpm, +"),
+        field(training.ramp, TrainingEdit::Ramp),
+        text("wpm per minute, sprint"),
+        field(training.drill_length, TrainingEdit::DrillLength),
+        text("s at"),
+        field(training.drill_boost, TrainingEdit::DrillBoost),
+        text("% every"),
+        field(training.drill_interval, TrainingEdit::DrillInterval),
+        text("s"),
+    ]
+    .align_y(Center)
+    .spacing(5)
+    .into()
+}
+
+fn warmup_settings_view(current_state: &State) -> Element<'_, Message> {
+    let warmup = current_state.get_warmup();
+    row![
+        text("Warm up over"),
+        text_input("0", &if warmup.words == 0 { String::new() } else { warmup.words.to_string() })
+        .on_input(|value| Message::EditWarmup(WarmupEdit::Words(value)))
+        .width(60),
+        text("words from"),
+        text_input("100", &warmup.start_speed.to_string())
+        .on_input(|value| Message::EditWarmup(WarmupEdit::StartSpeed(value)))
+        .width(60),
+        text("% speed after resuming, seeking or starting a chapter"),
+    ]
+    .align_y(Center)
+    .spacing(5)
+    .into()
+}
+
+fn auto_pause_view(current_state: &State) -> Element<'_, Message> {
+    let settings = current_state.get_auto_pause();
+    [(Boundary::Paragraph, "Paragraph end"), (Boundary::Section, "Section end"), (Boundary::Chapter, "Chapter end")].into_iter()
+    .fold(row![].spacing(15), |pauses, (boundary, label)| {
+        let pause = settings.get(boundary);
+        pauses.push(row![
+            text(label),
+            pick_list([PauseMode::Off, PauseMode::Timed, PauseMode::WaitForKey], Some(pause.mode), move |mode| Message::AutoPauseModeChanged(boundary, mode)),
+            (pause.mode == PauseMode::Timed).then(|| {
+                text_input("ms", &if pause.duration == 0 { String::new() } else { pause.duration.to_string() })
+                .on_input(move |duration| Message::AutoPauseDurationChanged(boundary, duration))
+                .width(70)
+            }),
+        ]
+        .align_y(Center)
+        .spacing(5))
+    })
+    .into()
+}
+
+// Revealed at the bottom of fullscreen while the mouse moves
+fn mini_controls(current_state: &State) -> Element<'_, Message> {
+    mouse_area(
+        row![
+            button("Previous sentence").on_press(Message::PreviousSentence),
+            button(if current_state.is_paused() { "Resume" } else { "Pause" }).on_press(Message::SpacePressed),
+            button("Next sentence").on_press(Message::NextSentence),
+            button("Slower").on_press(Message::ArrowDownPressed),
+            button("Faster").on_press(Message::ArrowUpPressed),
+            button("Exit fullscreen").on_press(Message::ToggleFullscreen(FullscreenKey::Escape)),
+        ]
+        .spacing(10)
+        .padding(10)
+    )
+    .on_enter(Message::ControlsHovered(true))
+    .on_exit(Message::ControlsHovered(false))
+    .into()
+}
+
+// Current training speed next to the title
+fn training_status(current_state: &State) -> Option<Element<'_, Message>> {
+    let (wpm, sprint) = current_state.get_training_speed()?;
+    let label = if sprint { format!("Sprint {} wpm", wpm) } else { format!("Training {} wpm", wpm) };
+    Some(text(label).size(10).into())
+}
+
+// Today's progress towards the daily goal, nothing without a goal
+fn goal_ring(current_state: &State, size: f32) -> Option<Element<'_, Message>> {
+    let (words, reading_ms) = current_state.get_today_reading();
+    let progress = current_state.get_goal().progress(words, reading_ms)?;
+    Some(tooltip(
+        canvas(ProgressRing { progress, width: size / 7.0 })
+        .width(size)
+        .height(size),
+        text(goal_label(current_state, words, reading_ms)).size(12),
+        tooltip::Position::Bottom,
+    )
+    .into())
+}
+
+fn goal_label(current_state: &State, words: usize, reading_ms: u64) -> String {
+    let goal = current_state.get_goal();
+    match goal.kind {
+        GoalKind::Minutes => format!("{}/{} min", reading_ms / 60_000, goal.amount),
+        GoalKind::Words => format!("{}/{} words", words, goal.amount),
     }
 }
 
+// Bars scaled to the largest value, with the value above and the label under each one
+fn bar_chart<'a>(bars: Vec<(String, f32)>) -> Element<'a, Message> {
+    let max = bars.iter().map(|bar| bar.1).fold(0.0, f32::max).max(1.0);
+    bars.into_iter()
+    .fold(row![].spacing(4).height(120), |chart, (label, value)| {
+        let filled = ((value / max) * 100.0).round() as u16;
+        chart.push(column![
+            Space::new().height(FillPortion(100 - filled)),
+            text(format!("{:.0}", value)).size(9),
+            container(Space::new())
+            .width(Fill)
+            .height(FillPortion(filled))
+            .style(|theme: &Theme| container::Style {
+                background: Some(theme.palette().primary.into()),
+                ..Default::default()
+            }),
+            text(label).size(9),
+        ]
+        .align_x(Center)
+        .width(Fill)
+        .height(Fill))
+    })
+    .into()
+}
+
+fn format_time(seconds: i64, format: &str) -> String {
+    chrono::Local.timestamp_opt(seconds, 0)
+    .earliest()
+    .map(|time| time.format(format).to_string())
+    .unwrap_or_default()
+}
+
+fn filters_view(current_state: &State) -> Element<'_, Message> {
+    let rules = current_state.get_filters().iter().enumerate()
+    .fold(column![].spacing(5), |rules, (i, rule)| {
+        let for_text = !current_state.text_meta.disabled_filters.contains(&rule.id);
+        rules.push(row![
+            checkbox(rule.enabled)
+            .on_toggle(move |_| Message::ToggleFilter(i)),
+            text_input("Name", &rule.name)
+            .on_input(move |name| Message::EditFilter(i, FilterEdit::Name(name)))
+            .width(180),
+            text_input("Pattern", &rule.pattern)
+            .on_input(move |pattern| Message::EditFilter(i, FilterEdit::Pattern(pattern))),
+            text_input("Replace with", &rule.replace)
+            .on_input(move |replace| Message::EditFilter(i, FilterEdit::Replace(replace)))
+            .width(100),
+            (!is_valid_filter(rule)).then(|| text("Invalid pattern")),
+            current_state.has_text().then(|| {
+                checkbox(for_text)
+                .label("This text")
+                .on_toggle(move |_| Message::ToggleTextFilter(i))
+            }),
+            button("Remove").on_press(Message::RemoveFilter(i)),
+        ]
+        .align_y(Center)
+        .spacing(10))
+    });
+
+    container(
+        column![
+            text("Filter rules, applied before the text is split into words"),
+            scrollable(rules).height(Fill),
+            row![
+                button("Add rule").on_press(Message::AddFilter),
+                button("Restore presets").on_press(Message::RestoreFilterPresets),
+                button("Done").on_press(Message::ApplyFilters),
+            ]
+            .spacing(10),
+        ]
+        .align_x(Center)
+        .spacing(10)
+        .padding(10)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
+// Preview of the boilerplate removed from the current text
+fn stripped_view(current_state: &State) -> Element<'_, Message> {
+    let sections = current_state.text_meta.stripped.iter()
+    .fold(column![].spacing(15), |sections, section| {
+        sections.push(column![
+            text(&section.label).size(18),
+            text(&section.text).size(12),
+        ].spacing(5))
+    });
+
+    container(
+        column![
+            text(format!("Removed from {}", current_state.get_title())),
+            scrollable(sections).height(Fill),
+            button("Back").on_press(Message::ShowReader),
+        ]
+        .align_x(Center)
+        .spacing(10)
+        .padding(10)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
+fn archive_picker_view(entries: &[ArchiveEntry]) -> Element<'_, Message> {
+    let files = entries.iter().enumerate()
+    .fold(column![].spacing(5), |files, (i, entry)| {
+        files.push(button(text(&entry.name)).on_press(Message::ArchiveEntrySelected(i)))
+    });
+
+    container(
+        column![
+            text(if entries.is_empty() { "No readable files in this archive" } else { "Choose a file to read" }),
+            scrollable(files).height(Fill),
+            button("Cancel").on_press(Message::ArchiveClosed),
+        ]
+        .align_x(Center)
+        .spacing(10)
+        .padding(10)
+    )
+    .center_x(Fill)
+    .center_y(Fill)
+    .into()
+}
+
 fn color_pick_bg_view<'a>(display: bool, color_option_hex: &'a str, color_option: ColorOption) -> Element<'a, Message> {
     let button_label = text(format!("Set {} color", color_option));
     let button = button(button_label).on_press(Message::DisplayColor(color_option.clone()));